@@ -0,0 +1,96 @@
+// Compares the sparse and dense cell storage backends on a fully populated ZPL
+// palette. Run with `cargo bench`.
+
+extern crate color;
+extern crate palette;
+
+use palette::*;
+use palette::data::Data;
+
+use std::time::{Duration, Instant};
+
+
+/// The number of times each measurement is repeated.
+const ITERATIONS: u32 = 10;
+
+/// The number of free addresses left in the palette for `find_targets`.
+const FREE_COUNT: usize = 16;
+
+
+pub fn main() {
+	for &dense in &[false, true] {
+		let name = if dense {"dense"} else {"sparse"};
+
+		let build = measure(|| {full_palette(dense, 0);});
+		println!("{:>6} build:      {:?}", name, build);
+
+		let mut data = full_palette(dense, 0);
+		let evaluate = measure(|| evaluate_all(&mut data));
+		println!("{:>6} evaluation: {:?}", name, evaluate);
+
+		let mut data = full_palette(dense, FREE_COUNT);
+		let find = measure(|| {
+			data.find_targets(FREE_COUNT, Address::default(), false, None)
+				.unwrap();
+		});
+		println!("{:>6} find_targets({}): {:?}", name, FREE_COUNT, find);
+	}
+}
+
+
+/// Returns the average duration of the given function over `ITERATIONS` 
+/// runs.
+fn measure<F>(mut f: F) -> Duration where F: FnMut() {
+	let start = Instant::now();
+	for _ in 0..ITERATIONS {
+		f();
+	}
+	start.elapsed() / ITERATIONS
+}
+
+
+/// Creates a ZPL palette with every address filled except for the last `free`
+/// addresses. The main page holds colors, and every other page lightens the 
+/// corresponding cell of the main page.
+fn full_palette(dense: bool, free: usize) -> Data {
+	let mut data: Data = Default::default();
+	Format::Zpl.initialize(&mut data);
+	if !dense {
+		data.cells.make_sparse();
+	}
+
+	let addresses: Vec<_> = Reference::all()
+		.resolve(&data, Address::default())
+		.unwrap()
+		.iter(&data)
+		.collect();
+	let filled = addresses.len() - free;
+
+	for &address in &addresses[..filled] {
+		let expression = if address.page == 0 {
+			Expression::Color(Color::new(
+				address.line * 16, 
+				address.column * 16, 
+				0x80))
+		} else {
+			let source = Address::new(0, address.line % 14, address.column);
+			format!("lighten({}, 5%)", source).parse().unwrap()
+		};
+		data.create_cell(address).unwrap();
+		data.set_expression(address, expression).unwrap();
+	}
+	data
+}
+
+
+/// Invalidates every color on the main page and evaluates every cell.
+fn evaluate_all(data: &mut Data) {
+	for line in 0..14 {
+		for column in 0..16 {
+			data.invalidate(Address::new(0, line, column));
+		}
+	}
+	for (address, _) in data.cells.iter() {
+		data.color(address);
+	}
+}
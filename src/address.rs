@@ -37,16 +37,21 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 // Local imports.
+use data::Data;
+use dependency::Coverage;
 use result::{
 	Result,
 	Error,
 };
 
 // Non-local imports.
-use interval::Interval;
+use interval::{self, Interval};
 
 // Standard imports.
+use std::collections;
 use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
 use std::u16;
 use std::u8;
 use std::ops::Add;
@@ -158,6 +163,127 @@ impl Reference {
 		}
 	}
 
+	/// Returns a `Reference` to the cells given by the named reference.
+	pub fn named<S>(name: S) -> Reference where S: Into<String> {
+		use self::ReferenceComponent::*;
+
+		let name = name.into();
+		Reference {
+			page: Named(name.clone()),
+			line: Named(name.clone()),
+			column: Named(name),
+		}
+	}
+
+	/// Returns a `Reference` to the cell at the given offsets from the base 
+	/// address it is resolved against. When used in an expression, the base
+	/// address is the address of the cell holding the expression, so the
+	/// reference moves along with the expression when it is copied elsewhere.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::colorspace::ColorSpace;
+	/// use palette::operation::*;
+	///
+	/// // One column to the left, and the same column on the previous line.
+	/// let left = Reference::relative(0, 0, -1);
+	/// let above = Reference::relative(0, -1, 0);
+	/// assert_eq!(left, "_:_:_-1".parse().unwrap());
+	/// assert_eq!(above, "_/_-1/_".parse().unwrap());
+	///
+	/// // A ramp which lightens each color from the one to its left.
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// for &line in &[0, 1] {
+	/// 	pal.apply(Box::new(InsertCell::new()
+	/// 		.located_at(Address::new(0, line, 0))
+	/// 		.with_expression(Expression::Color(Color::new(0, 0, 0)))
+	/// 	)).unwrap();
+	/// 	for column in 1..4 {
+	/// 		pal.apply(Box::new(InsertCell::new()
+	/// 			.located_at(Address::new(0, line, column))
+	/// 			.with_expression(Expression::Blend(
+	/// 				left.clone(),
+	/// 				Address::new(0, 7, 0).into(),
+	/// 				0.5,
+	/// 				ColorSpace::Rgb))
+	/// 		)).unwrap();
+	/// 	}
+	/// }
+	/// pal.apply(Box::new(InsertCell::new()
+	/// 	.located_at(Address::new(0, 7, 0))
+	/// 	.with_expression(Expression::Color(Color::new(240, 240, 240)))
+	/// )).unwrap();
+	///
+	/// // Both copies of the ramp follow their own line.
+	/// assert_eq!(pal.color(Address::new(0, 0, 3)), 
+	/// 	Some(Color::new(210, 210, 210)));
+	/// assert_eq!(pal.color(Address::new(0, 1, 3)), 
+	/// 	Some(Color::new(210, 210, 210)));
+	///
+	/// pal.apply(Box::new(InsertCell::new()
+	/// 	.located_at(Address::new(0, 1, 0))
+	/// 	.overwrite(true)
+	/// 	.with_expression(Expression::Color(Color::new(240, 0, 0)))
+	/// )).unwrap();
+	/// assert_eq!(pal.color(Address::new(0, 0, 3)), 
+	/// 	Some(Color::new(210, 210, 210)));
+	/// assert_eq!(pal.color(Address::new(0, 1, 3)), 
+	/// 	Some(Color::new(240, 210, 210)));
+	/// ```
+	pub fn relative(
+		page: PageOffset, 
+		line: LineOffset, 
+		column: ColumnOffset) 
+		-> Reference
+	{
+		Reference {
+			page: ReferenceComponent::offset(page),
+			line: ReferenceComponent::offset(line),
+			column: ReferenceComponent::offset(column),
+		}
+	}
+
+	/// Returns a copy of the `Reference` with any components which are 
+	/// relative to a base address resolved against the given `Address`. Named
+	/// and `All` components are left unchanged.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidReferenceComponent` error when an offset would 
+	/// overflow or underflow the component boundaries.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	///
+	/// let reference = Reference::relative(0, -1, 2);
+	/// let base = Address::new(3, 4, 5);
+	///
+	/// assert_eq!(reference.anchored(base).unwrap(), 
+	/// 	Reference::from(Address::new(3, 3, 7)));
+	/// assert_eq!("_/_/*".parse::<Reference>().unwrap().anchored(base).unwrap(),
+	/// 	Reference::line_of(&base));
+	/// assert!(reference.anchored(Address::new(0, 0, 0)).is_err());
+	/// ```
+	pub fn anchored(&self, base: Address) -> Result<Reference> {
+		let mut anchored = self.clone();
+		anchored.page.resolve_index_indirection(base.page)?;
+		anchored.line.resolve_index_indirection(base.line)?;
+		anchored.column.resolve_index_indirection(base.column)?;
+		Ok(anchored)
+	}
+
+	/// Returns whether any component of the `Reference` is relative to the
+	/// base address it is resolved against.
+	pub fn is_relative(&self) -> bool {
+		self.page.is_relative() || 
+		self.line.is_relative() || 
+		self.column.is_relative()
+	}
+
 	/// Returns a `Reference` to the page containing the given `Address`.
 	pub fn page_of(addr: &Address) -> Reference {
 		use self::ReferenceComponent::*;
@@ -227,6 +353,252 @@ impl Reference {
 			Err(Error::UnresolvedReferenceComponent)
 		}
 	} 
+
+	/// Returns the name being referenced if the `Reference` was constructed
+	/// using `Reference::named`.
+	pub fn as_name(&self) -> Option<&str> {
+		use self::ReferenceComponent::*;
+
+		match *self {
+			Reference {
+				page: Named(ref page),
+				line: Named(ref line),
+				column: Named(ref column),
+			} if page == line && line == column => Some(&page[..]),
+			_ => None,
+		}
+	}
+
+	/// Writes the components of the `Reference` joined by the given separator.
+	pub(crate) fn write_separated(
+		&self,
+		f: &mut fmt::Formatter,
+		separator: char)
+		-> fmt::Result
+	{
+		write!(f, "{}{}{}{}{}",
+			self.page, separator,
+			self.line, separator,
+			self.column)
+	}
+
+	/// Returns the `Address` being referenced.
+	///
+	/// # Errors
+	///
+	/// Returns an `UnresolvedReferenceComponent` error when the reference does
+	/// not resolve to a single address.
+	pub fn address(&self) -> Result<Address> {
+		Ok(Address::new(self.page()?, self.line()?, self.column()?))
+	}
+
+	/// Returns whether any component of the `Reference` refers to the given
+	/// name.
+	pub fn uses_name(&self, name: &str) -> bool {
+		self.page.uses_name(name) ||
+		self.line.uses_name(name) ||
+		self.column.uses_name(name)
+	}
+
+	/// Replaces the given name with a new name in every component of the 
+	/// `Reference`. Returns whether any components were changed.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Reference;
+	///
+	/// let mut reference = Reference::named("skin");
+	/// assert!(reference.rename("skin", "flesh"));
+	///
+	/// assert_eq!(reference, Reference::named("flesh"));
+	/// assert!(!reference.uses_name("skin"));
+	/// ```
+	pub fn rename(&mut self, old: &str, new: &str) -> bool {
+		let page = self.page.rename(old, new);
+		let line = self.line.rename(old, new);
+		let column = self.column.rename(old, new);
+		page || line || column
+	}
+
+	/// Returns whether the given `Address` is directly referenced. Only 
+	/// indexed and `All` components are matched, so this will always return 
+	/// false for references with any other components.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	///
+	/// let line = Reference::line_of(&Address::new(0, 3, 0));
+	///
+	/// assert!(line.matches(&Address::new(0, 3, 12)));
+	/// assert!(!line.matches(&Address::new(0, 4, 12)));
+	/// ```
+	pub fn matches(&self, address: &Address) -> bool {
+		self.page.matches(&address.page) &&
+		self.line.matches(&address.line) &&
+		self.column.matches(&address.column)
+	}
+
+	/// Resolves the `Reference` into a `Selection` of concrete addresses in the
+	/// given `Data`. `Any` components resolve to the corresponding component of 
+	/// the `base` address, and `Indirect` components are offset from it. 
+	/// `Named` components are resolved through the `Data`'s names, and `All`
+	/// components expand to every page, line, or column in the `Data`'s 
+	/// layout.
+	///
+	/// A selection of whole pages may include addresses past the last column
+	/// of each line, but never addresses which are past the last line or page.
+	///
+	/// # Errors
+	///
+	/// Returns an `UndefinedName` error if a referenced name is undefined, a 
+	/// `CyclicName` error if a name is defined in terms of itself, an 
+	/// `InvalidReferenceComponent` error if an offset overflows, and an 
+	/// `InvalidReference` error if the reference resolves to a single address 
+	/// outside of the palette's layout.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	/// use palette::data::Data;
+	/// use palette::layout::GridLayout;
+	/// use palette::sync::Shared;
+	///
+	/// let mut dat: Data = Default::default();
+	/// dat.set_line_count(Reference::page_of(&Address::new(0, 0, 0)), 4)
+	/// 	.unwrap();
+	/// dat.set_column_count(Reference::line_of(&Address::new(0, 3, 0)), 8)
+	/// 	.unwrap();
+	/// let ramp = Reference::line_of(&Address::new(0, 3, 0));
+	/// dat.names.insert("ramp".into(), ramp);
+	///
+	/// let base = Address::new(0, 0, 0);
+	/// let line = Reference::named("ramp").resolve(&dat, base).unwrap();
+	///
+	/// assert!(line.contains(&Address::new(0, 3, 7)));
+	/// assert!(!line.contains(&Address::new(0, 3, 8)));
+	/// assert!(!line.contains(&Address::new(0, 2, 0)));
+	///
+	/// assert!(Reference::from(Address::new(0, 4, 0))
+	/// 	.resolve(&dat, base)
+	/// 	.is_err());
+	/// assert!(Reference::named("undefined").resolve(&dat, base).is_err());
+	///
+	/// dat.layout = Shared::new(GridLayout::new(2, 4, 8));
+	/// let last_page = Reference::page_of(&Address::new(1, 0, 0));
+	/// assert!(last_page.resolve(&dat, base).unwrap()
+	/// 	.contains(&Address::new(1, 3, 7)));
+	/// let outside = Reference::page_of(&Address::new(2, 0, 0));
+	/// assert!(!outside.resolve(&dat, base).unwrap()
+	/// 	.contains(&Address::new(2, 0, 0)));
+	/// ```
+	pub fn resolve(&self, data: &Data, base: Address) -> Result<Selection> {
+		let (page, line, column) = self.resolve_components(data, base)?;
+
+		// Only pages within the layout are selected.
+		let page_count = data.layout.page_count();
+		let (first, last) = match page {
+			Some(page) => (page, page),
+			None => (0, page_count.saturating_sub(1)),
+		};
+		let pages = (first..=last).filter(|&page| page < page_count);
+
+		// A reference to the entire palette is a single interval.
+		if let (None, None, None) = (page, line, column) {
+			let mut selection = Selection::default();
+			if let Some(last) = pages.clone().rev()
+				.filter_map(|page| last_address_of_page(data, page))
+				.next()
+			{
+				selection.union(Interval::closed(Address::new(0, 0, 0), last));
+			}
+			return Ok(selection);
+		}
+
+		let mut intervals = Vec::new();
+		for page in pages {
+			let line_count = data.line_count(&Reference::page_of(
+				&Address::new(page, 0, 0)));
+			let column_count = |line| data.column_count(&Reference::line_of(
+				&Address::new(page, line, 0)));
+
+			match (line, column) {
+				(None, None) => if let Some(last) 
+					= last_address_of_page(data, page) 
+				{
+					intervals.push(Interval::closed(
+						Address::new(page, 0, 0), 
+						last));
+				},
+
+				(Some(line), None) => {
+					let columns = column_count(line);
+					if line < line_count && columns > 0 {
+						intervals.push(Interval::closed(
+							Address::new(page, line, 0), 
+							Address::new(page, line, columns - 1)));
+					}
+				},
+
+				(None, Some(column)) => for line in 0..line_count {
+					if column < column_count(line) {
+						let address = Address::new(page, line, column);
+						intervals.push(Interval::closed(address, address));
+					}
+				},
+
+				(Some(line), Some(column)) => {
+					if line < line_count && column < column_count(line) {
+						let address = Address::new(page, line, column);
+						intervals.push(Interval::closed(address, address));
+					}
+				},
+			}
+		}
+
+		if intervals.is_empty() && page.is_some() && 
+			line.is_some() && column.is_some() 
+		{
+			return Err(Error::InvalidReference(self.clone()));
+		}
+		Ok(Selection::new(intervals))
+	}
+
+	/// Resolves the page, line, and column of the reference relative to the 
+	/// given base address, following any names. A component is `None` if it 
+	/// selects every page, line, or column. Unlike `resolve`, the result does
+	/// not depend on the palette's layout.
+	pub(crate) fn resolve_components(&self, data: &Data, base: Address) 
+		-> Result<Coverage>
+	{
+		Ok((
+			self.page
+				.resolve(data, base.page, &|r| &r.page, &mut Vec::new())?,
+			self.line
+				.resolve(data, base.line, &|r| &r.line, &mut Vec::new())?,
+			self.column
+				.resolve(data, base.column, &|r| &r.column, &mut Vec::new())?,
+		))
+	}
+}
+
+
+/// Returns the last address on the given page, or `None` if the page has no
+/// valid addresses.
+fn last_address_of_page(data: &Data, page: Page) -> Option<Address> {
+	let line_count = data.line_count(&Reference::page_of(
+		&Address::new(page, 0, 0)));
+	if line_count == 0 {return None;}
+
+	let line = line_count - 1;
+	let column_count = data.column_count(&Reference::line_of(
+		&Address::new(page, line, 0)));
+	if column_count == 0 {return None;}
+
+	Some(Address::new(page, line, column_count - 1))
 }
 
 
@@ -243,6 +615,147 @@ impl From<Address> for Reference {
 }
 
 
+/// Parses a `Reference` from the format produced by its `Display` 
+/// implementation. Components may be `*` for all indices, `_` for the index of
+/// the base address, a name, or a decimal or `0x`-prefixed hexadecimal index. 
+/// Any component other than `*` may be followed by a `+n` or `-n` offset. The
+/// components may be separated by `:` instead of `/`, and `@name` is parsed as
+/// a reference to the given name.
+///
+/// # Errors
+///
+/// Returns a `MalformedReference` error if the text is not a valid reference.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::{Address, Reference};
+///
+/// for &text in &["1/_/*", "0x10/ramp/3", "_+1/2/_-1", "skin/skin/skin"] {
+/// 	let reference: Reference = text.parse().unwrap();
+/// 	assert_eq!(reference.to_string().parse::<Reference>().unwrap(), 
+/// 		reference);
+/// }
+/// assert_eq!("2/_-1/0".parse::<Reference>().unwrap().to_string(), "2/_-1/0");
+///
+/// assert_eq!("1:2:3".parse::<Reference>().unwrap(), 
+/// 	Reference::from(Address::new(1, 2, 3)));
+/// assert_eq!("@skin".parse::<Reference>().unwrap(), Reference::named("skin"));
+/// assert!("1/2".parse::<Reference>().is_err());
+/// assert!("1/256/0".parse::<Reference>().is_err());
+/// ```
+impl FromStr for Reference {
+	type Err = Error;
+
+	fn from_str(text: &str) -> Result<Self> {
+		let malformed = || Error::MalformedReference(text.to_owned());
+
+		if text.starts_with('@') {
+			return if is_name(&text[1..]) {
+				Ok(Reference::named(&text[1..]))
+			} else {
+				Err(malformed())
+			};
+		}
+
+		let separator = if text.contains('/') {'/'} else {':'};
+		let parts: Vec<_> = text.split(separator).collect();
+		if parts.len() != 3 {return Err(malformed());}
+
+		Ok(Reference {
+			page: parse_component(parts[0], u16::from_str_radix, 
+				i32::from_str_radix).ok_or_else(&malformed)?,
+			line: parse_component(parts[1], u8::from_str_radix, 
+				i16::from_str_radix).ok_or_else(&malformed)?,
+			column: parse_component(parts[2], u8::from_str_radix, 
+				i16::from_str_radix).ok_or_else(&malformed)?,
+		})
+	}
+}
+
+
+/// A function for parsing a number with a given radix.
+type RadixParser<T> = fn(&str, u32) -> ::std::result::Result<T, ParseIntError>;
+
+
+/// Parses a `ReferenceComponent`, returning `None` if the text is invalid.
+fn parse_component<T, O>(
+	text: &str, 
+	index: RadixParser<T>,
+	offset: RadixParser<O>)
+	-> Option<ReferenceComponent<T, O>>
+{
+	if text == "*" {return Some(ReferenceComponent::All);}
+
+	match text.find(|c| c == '+' || c == '-') {
+		Some(0) => None,
+		Some(position) => {
+			let (base, offset_text) = text.split_at(position);
+			let sign = &offset_text[..1];
+			let magnitude = &offset_text[1..];
+			let (digits, radix) = split_radix(magnitude)?;
+			let offset = offset(&format!("{}{}", sign, digits), radix).ok()?;
+			parse_direct_component(base, index)
+				.map(|direct| ReferenceComponent::Indirect(direct, offset))
+		},
+		None => parse_direct_component(text, index)
+			.map(ReferenceComponent::from),
+	}
+}
+
+
+/// Parses a `DirectReferenceComponent`, returning `None` if the text is 
+/// invalid.
+fn parse_direct_component<T>(text: &str, index: RadixParser<T>) 
+	-> Option<DirectReferenceComponent<T>>
+{
+	if text == "_" {
+		Some(DirectReferenceComponent::Any)
+	} else if text.starts_with(|c: char| c.is_digit(10)) {
+		parse_index(text, index).map(DirectReferenceComponent::Index)
+	} else if is_name(text) {
+		Some(DirectReferenceComponent::Named(text.to_owned()))
+	} else {
+		None
+	}
+}
+
+
+/// Parses a decimal or `0x`-prefixed hexadecimal index.
+fn parse_index<T>(text: &str, index: RadixParser<T>) -> Option<T> {
+	let (digits, radix) = split_radix(text)?;
+	index(digits, radix).ok()
+}
+
+
+/// Splits the radix prefix from a number, returning the digits and radix, or 
+/// `None` if the digits are invalid.
+fn split_radix(text: &str) -> Option<(&str, u32)> {
+	let (digits, radix) = if text.starts_with("0x") || text.starts_with("0X") {
+		(&text[2..], 16)
+	} else {
+		(text, 10)
+	};
+
+	if !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix)) {
+		Some((digits, radix))
+	} else {
+		None
+	}
+}
+
+
+/// Returns whether the given text is a valid name for a `Reference`. Names
+/// consist of letters, digits, underscores and periods, and must not begin 
+/// with a digit.
+pub(crate) fn is_name(text: &str) -> bool {
+	text != "_" &&
+	!text.starts_with(|c: char| c.is_digit(10) || c == '.') &&
+	!text.is_empty() &&
+	text.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+
 impl Default for Reference {
 	fn default() -> Self {
 		use self::ReferenceComponent::*;
@@ -258,7 +771,7 @@ impl Default for Reference {
 
 impl fmt::Display for Reference {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}/{}/{}", self.page, self.line, self.column)
+		self.write_separated(f, '/')
 	}
 }
 
@@ -273,7 +786,6 @@ enum ReferenceComponent<T, O> {
 	Any,
 	Index(T),
 	Named(String),
-	#[allow(dead_code)]
 	Indirect(DirectReferenceComponent<T>, O),
 	All,
 }
@@ -283,6 +795,18 @@ impl<T, O> ReferenceComponent<T, O>
 		O: Offset<Base=T>,
 		T: Add
 {
+	/// Returns a component at the given offset from the base index, or `Any`
+	/// if the offset is zero.
+	fn offset(offset: O) -> Self where O: PartialEq + Default {
+		use self::ReferenceComponent::*;
+
+		if offset == O::default() {
+			Any
+		} else {
+			Indirect(DirectReferenceComponent::Any, offset)
+		}
+	}
+
 	/// Resolves index-relative components to their absolute positions, relative
 	/// to the given base index. 
 	///
@@ -290,12 +814,13 @@ impl<T, O> ReferenceComponent<T, O>
 	///
 	/// Returns an `InvalidReferenceComponent` error when the offset would
 	/// overflow or underflow the component boundaries.
-	#[allow(dead_code)]
 	pub fn resolve_index_indirection(&mut self, base: T) -> Result<()> {
 		use self::ReferenceComponent::*;
 
 		let mut resolved = None;
-		if let Indirect(ref drc, ref o) = *self {
+		if let Any = *self {
+			resolved = Some(base);
+		} else if let Indirect(ref drc, ref o) = *self {
 			resolved = match *drc {
 				DirectReferenceComponent::Any
 					=> Some(o.offset(&base)?),
@@ -314,6 +839,133 @@ impl<T, O> ReferenceComponent<T, O>
 	}
 }
 
+impl<T, O> ReferenceComponent<T, O> {
+	/// Returns whether the component is relative to the base index.
+	fn is_relative(&self) -> bool {
+		use self::ReferenceComponent::*;
+
+		match *self {
+			Any |
+			Indirect(DirectReferenceComponent::Any, _) => true,
+			_ => false,
+		}
+	}
+
+	/// Returns whether the component refers to the given name.
+	fn uses_name(&self, name: &str) -> bool {
+		use self::ReferenceComponent::*;
+
+		match *self {
+			Named(ref n) |
+			Indirect(DirectReferenceComponent::Named(ref n), _) => n == name,
+			_ => false,
+		}
+	}
+
+	/// Replaces the given name with a new name. Returns whether the component
+	/// was changed.
+	fn rename(&mut self, old: &str, new: &str) -> bool {
+		use self::ReferenceComponent::*;
+
+		match *self {
+			Named(ref mut n) |
+			Indirect(DirectReferenceComponent::Named(ref mut n), _) => {
+				if n == old {
+					*n = new.to_owned();
+					true
+				} else {
+					false
+				}
+			},
+			_ => false,
+		}
+	}
+}
+
+impl<T, O> ReferenceComponent<T, O> where T: PartialEq {
+	/// Returns whether the component directly matches the given index.
+	fn matches(&self, index: &T) -> bool {
+		use self::ReferenceComponent::*;
+
+		match *self {
+			Index(ref i)	=> i == index,
+			All				=> true,
+			_				=> false,
+		}
+	}
+}
+
+impl<T, O> ReferenceComponent<T, O>
+	where 
+		O: Offset<Base=T>,
+		T: Add + Copy,
+{
+	/// Resolves the component to a single index, or `None` if it refers to all
+	/// indices. The `select` function extracts the corresponding component
+	/// from named references, and `visited` records the names being resolved.
+	///
+	/// # Errors
+	///
+	/// Returns an `UndefinedName` error if a name is undefined, a 
+	/// `CyclicName` error if a name is defined in terms of itself, and an 
+	/// `InvalidReferenceComponent` error if an offset overflows.
+	fn resolve<F>(
+		&self, 
+		data: &Data, 
+		base: T, 
+		select: &F,
+		visited: &mut Vec<String>)
+		-> Result<Option<T>>
+		where F: Fn(&Reference) -> &ReferenceComponent<T, O>
+	{
+		use self::ReferenceComponent::*;
+
+		match *self {
+			Any				=> Ok(Some(base)),
+			Index(i)		=> Ok(Some(i)),
+			All				=> Ok(None),
+			Named(ref name)	=> resolve_name(name, data, base, select, visited),
+			Indirect(ref drc, offset) => {
+				let indirect_base = match *drc {
+					DirectReferenceComponent::Any => base,
+					DirectReferenceComponent::Index(i) => i,
+					DirectReferenceComponent::Named(ref name) => 
+						resolve_name(name, data, base, select, visited)?
+							.ok_or(Error::UnresolvedReferenceComponent)?,
+				};
+				offset.offset(&indirect_base).map(Some)
+			},
+		}
+	}
+}
+
+/// Resolves the component selected from the named reference to a single 
+/// index, or `None` if it refers to all indices.
+fn resolve_name<T, O, F>(
+	name: &str,
+	data: &Data, 
+	base: T, 
+	select: &F,
+	visited: &mut Vec<String>)
+	-> Result<Option<T>>
+	where
+		O: Offset<Base=T>,
+		T: Add + Copy,
+		F: Fn(&Reference) -> &ReferenceComponent<T, O>
+{
+	if visited.iter().any(|visited| visited == name) {
+		return Err(Error::CyclicName(name.to_owned()));
+	}
+	let named = data.names
+		.get(name)
+		.ok_or_else(|| Error::UndefinedName(name.to_owned()))?;
+
+	visited.push(name.to_owned());
+	let resolved = select(named).resolve(data, base, select, visited);
+	visited.pop();
+	resolved
+}
+
 impl<T, O> From<DirectReferenceComponent<T>> for ReferenceComponent<T, O> {
 	fn from(drc: DirectReferenceComponent<T>) -> Self {
 		use self::DirectReferenceComponent::*;
@@ -411,8 +1063,89 @@ impl Address {
 		}
 	}
 
+	/// Returns the next `Address` in address order, or `None` if this is the 
+	/// last possible address. This does not take any palette layout into 
+	/// account.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	///
+	/// assert_eq!(Address::new(0, 0, 255).successor(), 
+	/// 	Some(Address::new(0, 1, 0)));
+	/// assert_eq!(Address::new(0, 1, 0).predecessor(), 
+	/// 	Some(Address::new(0, 0, 255)));
+	/// assert_eq!(Address::new(0, 0, 0).predecessor(), None);
+	/// ```
+	pub fn successor(&self) -> Option<Address> {
+		if self.column < COLUMN_MAX {
+			Some(Address::new(self.page, self.line, self.column + 1))
+		} else if self.line < LINE_MAX {
+			Some(Address::new(self.page, self.line + 1, 0))
+		} else if self.page < PAGE_MAX {
+			Some(Address::new(self.page + 1, 0, 0))
+		} else {
+			None
+		}
+	}
+
+	/// Returns the previous `Address` in address order, or `None` if this is 
+	/// the first possible address. This does not take any palette layout into
+	/// account.
+	pub fn predecessor(&self) -> Option<Address> {
+		if self.column > 0 {
+			Some(Address::new(self.page, self.line, self.column - 1))
+		} else if self.line > 0 {
+			Some(Address::new(self.page, self.line - 1, COLUMN_MAX))
+		} else if self.page > 0 {
+			Some(Address::new(self.page - 1, LINE_MAX, COLUMN_MAX))
+		} else {
+			None
+		}
+	}
+
+	/// Parses an `Address` whose components are all given in the given radix,
+	/// such as the output of its `UpperHex` and `LowerHex` implementations.
+	///
+	/// # Errors
+	///
+	/// Returns a `MalformedReference` error if the text is not a valid address.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	///
+	/// let address = Address::new(300, 12, 9);
+	/// let upper = format!("{:X}", address);
+	/// let lower = format!("{:x}", address);
+	///
+	/// assert_eq!(upper, "12C:0C:09");
+	/// assert_eq!(Address::from_str_radix(&upper, 16).unwrap(), address);
+	/// assert_eq!(Address::from_str_radix(&lower, 16).unwrap(), address);
+	/// ```
+	pub fn from_str_radix(text: &str, radix: u32) -> Result<Self> {
+		let malformed = || Error::MalformedReference(text.to_owned());
+		let parts: Vec<_> = text.split(':').collect();
+		let digits = |part: &&str| !part.is_empty() && 
+			part.chars().all(|c| c.is_digit(radix));
+		if parts.len() != 3 || !parts.iter().all(digits) {
+			return Err(malformed());
+		}
+
+		Ok(Address::new(
+			u16::from_str_radix(parts[0], radix).map_err(|_| malformed())?,
+			u8::from_str_radix(parts[1], radix).map_err(|_| malformed())?,
+			u8::from_str_radix(parts[2], radix).map_err(|_| malformed())?,
+		))
+	}
+
 	/// Returns the `Address` n steps ahead, assuming the given wrapping 
-	/// parameters.
+	/// parameters. This assumes every page and line has the same size; use
+	/// `Data::index_of` and `Data::address_of` to step through a palette's
+	/// actual layout. If any of the counts is zero, there are no addresses to
+	/// step to, and the address is returned unchanged.
 	///
 	/// # Example
 	///
@@ -426,6 +1159,8 @@ impl Address {
 	///
 	/// let c = Address::new(0, 0, 0).wrapping_step(200, 5, 5, 5);
 	/// assert_eq!(c, Address::new(3, 0, 0));
+	///
+	/// assert_eq!(a.wrapping_step(1, 10, 0, 10), a);
 	/// ```
 	pub fn wrapping_step(
 		&self, 
@@ -435,6 +1170,8 @@ impl Address {
 		columns: Column) 
 		-> Address
 	{
+		if pages == 0 || lines == 0 || columns == 0 {return *self;}
+
 		let (l, c) = (lines as usize, columns as usize);
 		let n2 = n 
 			+ self.page as usize * l * c
@@ -479,12 +1216,74 @@ impl fmt::LowerHex for Address {
 }
 
 
+/// Parses an `Address` from the format produced by its `Display` 
+/// implementation. Components may also be given in hexadecimal with a `0x` 
+/// prefix.
+///
+/// # Errors
+///
+/// Returns a `MalformedReference` error if the text is not a valid address.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Address;
+///
+/// let address = Address::new(300, 12, 9);
+/// assert_eq!(address.to_string().parse::<Address>().unwrap(), address);
+/// assert_eq!("0x12C:0xc:9".parse::<Address>().unwrap(), address);
+/// assert!("1:2".parse::<Address>().is_err());
+/// ```
+impl FromStr for Address {
+	type Err = Error;
+
+	fn from_str(text: &str) -> Result<Self> {
+		let malformed = || Error::MalformedReference(text.to_owned());
+
+		let parts: Vec<_> = text.split(':').collect();
+		if parts.len() != 3 {return Err(malformed());}
+
+		Ok(Address::new(
+			parse_index(parts[0], u16::from_str_radix).ok_or_else(&malformed)?,
+			parse_index(parts[1], u8::from_str_radix).ok_or_else(&malformed)?,
+			parse_index(parts[2], u8::from_str_radix).ok_or_else(&malformed)?,
+		))
+	}
+}
+
+
 
 
 ////////////////////////////////////////////////////////////////////////////////
 // Selection
 ////////////////////////////////////////////////////////////////////////////////
 /// A possibly non-contiguous selection of addresses.
+///
+/// Selections are kept normalized, so that their intervals are sorted, and no
+/// two intervals overlap or are adjacent.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Address;
+/// use palette::address::Selection;
+///
+/// let a = Selection::range(Address::new(0, 0, 0), Address::new(0, 0, 9));
+/// let b = Selection::range(Address::new(0, 0, 5), Address::new(0, 1, 0));
+///
+/// let both = a.intersection(&b);
+/// assert!(both.contains(&Address::new(0, 0, 5)));
+/// assert!(!both.contains(&Address::new(0, 0, 4)));
+///
+/// let only_a = a.difference(&b);
+/// assert!(only_a.contains(&Address::new(0, 0, 4)));
+/// assert!(!only_a.contains(&Address::new(0, 0, 5)));
+///
+/// let either = a.symmetric_difference(&b);
+/// assert!(either.contains(&Address::new(0, 0, 0)));
+/// assert!(!either.contains(&Address::new(0, 0, 9)));
+/// assert!(either.contains(&Address::new(0, 1, 0)));
+/// ```
 #[derive(Default, Clone)]
 pub struct Selection {
 	inner: Vec<Interval<Address>>
@@ -496,18 +1295,356 @@ impl Selection {
 	pub fn new<I>(intervals: I) -> Self 
 		where I: IntoIterator<Item=Interval<Address>> 
 	{
-		Selection {
+		let mut selection = Selection {
 			inner: Interval::union_all(intervals.into_iter())
+		};
+		selection.normalize();
+		selection
+	}
+
+	/// Creates a new selection containing the addresses between `start` and 
+	/// `end`, inclusive.
+	pub fn range(start: Address, end: Address) -> Self {
+		Selection::new(Some(Interval::closed(start, end)))
+	}
+
+	/// Creates a new selection containing a rectangular block of addresses 
+	/// spanning the given inclusive ranges of pages, lines and columns. The 
+	/// block is clipped to the line and column counts of each page and line in
+	/// the given `Data`, so lines and columns of differing lengths are handled
+	/// correctly.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	/// use palette::address::Selection;
+	/// use palette::data::Data;
+	///
+	/// let mut dat: Data = Default::default();
+	/// dat.set_column_count(Reference::line_of(&Address::new(10, 3, 0)), 6)
+	/// 	.unwrap();
+	/// 
+	/// // Columns 4-7 of lines 2-5 on pages 10-20.
+	/// let block = Selection::block(&dat, (10, 20), (2, 5), (4, 7));
+	///
+	/// assert!(block.contains(&Address::new(10, 2, 4)));
+	/// assert!(block.contains(&Address::new(20, 5, 7)));
+	/// assert!(!block.contains(&Address::new(10, 2, 8)));
+	/// assert!(!block.contains(&Address::new(10, 3, 0)));
+	/// assert!(!block.contains(&Address::new(21, 2, 4)));
+	///
+	/// // Line 10:3 only has 6 columns.
+	/// assert!(block.contains(&Address::new(10, 3, 5)));
+	/// assert!(!block.contains(&Address::new(10, 3, 6)));
+	/// assert_eq!(block.iter(&dat).count(), 11 * 4 * 4 - 2);
+	/// ```
+	pub fn block(
+		data: &Data,
+		pages: (Page, Page),
+		lines: (Line, Line),
+		columns: (Column, Column))
+		-> Self
+	{
+		let mut intervals = Vec::new();
+		if pages.0 > pages.1 || lines.0 > lines.1 || columns.0 > columns.1 {
+			return Selection::default();
+		}
+
+		for page in pages.0..pages.1.saturating_add(1) {
+			if page >= data.layout.page_count() {break;}
+			let line_count = data.line_count(
+				&Reference::page_of(&Address::new(page, 0, 0)));
+
+			for line in lines.0..lines.1.saturating_add(1) {
+				if line >= line_count {break;}
+				let column_count = data.column_count(
+					&Reference::line_of(&Address::new(page, line, 0)));
+				if columns.0 >= column_count {continue;}
+
+				let last = if columns.1 < column_count {
+					columns.1
+				} else {
+					column_count - 1
+				};
+				intervals.push(Interval::closed(
+					Address::new(page, line, columns.0),
+					Address::new(page, line, last)));
+			}
 		}
+		Selection::new(intervals)
 	}
 
 	/// Unions an interval into the selection.
 	pub fn union(&mut self, interval: Interval<Address>) {
 		self.inner.push(interval);
+		self.normalize();
 	}
 
 	/// Returns whether the given address is contained in the selection.
 	pub fn contains(&self, address: &Address) -> bool {
 		self.inner.iter().any(|int| int.contains(address))
 	}
-}
\ No newline at end of file
+
+	/// Returns whether the selection contains no addresses.
+	pub fn is_empty(&self) -> bool {
+		self.inner.is_empty()
+	}
+
+	/// Sorts the intervals of the selection and merges any which overlap or 
+	/// are adjacent.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # extern crate palette;
+	/// # extern crate interval;
+	/// # fn main() {
+	/// use palette::Address;
+	/// use palette::address::Selection;
+	/// use interval::Interval;
+	///
+	/// let mut selection = Selection::range(
+	/// 	Address::new(0, 2, 0), 
+	/// 	Address::new(0, 2, 255));
+	/// selection.union(Interval::closed(
+	/// 	Address::new(0, 1, 0), 
+	/// 	Address::new(0, 1, 255)));
+	/// selection.union(Interval::closed(
+	/// 	Address::new(0, 1, 3), 
+	/// 	Address::new(0, 1, 9)));
+	///
+	/// assert_eq!(selection.ranges(), vec![
+	/// 	(Address::new(0, 1, 0), Address::new(0, 2, 255))
+	/// ]);
+	/// # }
+	/// ```
+	pub fn normalize(&mut self) {
+		let mut ranges: Vec<_> = self.inner
+			.iter()
+			.filter_map(closed_range)
+			.collect();
+		ranges.sort();
+
+		let mut merged: Vec<(Address, Address)> = Vec::new();
+		for (start, end) in ranges {
+			if let Some(last) = merged.last_mut() {
+				if last.1 >= start || last.1.successor() == Some(start) {
+					if end > last.1 {last.1 = end;}
+					continue;
+				}
+			}
+			merged.push((start, end));
+		}
+		*self = Selection::from_ranges(merged);
+	}
+
+	/// Returns the inclusive start and end addresses of each interval in the
+	/// selection, in order.
+	pub fn ranges(&self) -> Vec<(Address, Address)> {
+		self.inner.iter().filter_map(closed_range).collect()
+	}
+
+	/// Returns the selection of addresses in both `self` and `other`.
+	pub fn intersection(&self, other: &Selection) -> Selection {
+		let (a, b) = (self.ranges(), other.ranges());
+		let (mut i, mut j) = (0, 0);
+		let mut result = Vec::new();
+
+		while i < a.len() && j < b.len() {
+			let start = if a[i].0 > b[j].0 {a[i].0} else {b[j].0};
+			let end = if a[i].1 < b[j].1 {a[i].1} else {b[j].1};
+			if start <= end {result.push((start, end));}
+			if a[i].1 < b[j].1 {i += 1;} else {j += 1;}
+		}
+		Selection::from_ranges(result)
+	}
+
+	/// Returns the selection of addresses in `self` but not in `other`.
+	pub fn difference(&self, other: &Selection) -> Selection {
+		let removed = other.ranges();
+		let mut result = Vec::new();
+
+		for (start, end) in self.ranges() {
+			let mut next = Some(start);
+			for &(r_start, r_end) in &removed {
+				let current = match next {
+					Some(current) if current <= end => current,
+					_ => break,
+				};
+				if r_end < current {continue;}
+				if r_start > end {break;}
+				if r_start > current {
+					result.push((current, r_start.predecessor()
+						.expect("address after another has a predecessor")));
+				}
+				next = r_end.successor();
+			}
+			if let Some(current) = next {
+				if current <= end {result.push((current, end));}
+			}
+		}
+		Selection::from_ranges(result)
+	}
+
+	/// Returns the selection of addresses in exactly one of `self` and 
+	/// `other`.
+	pub fn symmetric_difference(&self, other: &Selection) -> Selection {
+		let mut result = self.difference(other);
+		for interval in other.difference(self).inner {
+			result.inner.push(interval);
+		}
+		result.normalize();
+		result
+	}
+
+	/// Returns the selection of addresses within the layout of the given 
+	/// `Data` which are not in `self`. The selection holds an interval for 
+	/// each line of the layout, so addresses lying between the end of one line
+	/// and the start of the next are never included.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	/// use palette::address::Selection;
+	/// use palette::data::Data;
+	/// use palette::layout::GridLayout;
+	/// use palette::sync::Shared;
+	///
+	/// let mut dat: Data = Default::default();
+	/// dat.layout = Shared::new(GridLayout::new(1, 2, 2));
+	/// dat.set_column_count(Reference::line_of(&Address::new(0, 1, 0)), 3)
+	/// 	.unwrap();
+	///
+	/// let first: Selection = Address::new(0, 0, 0).into();
+	/// let rest: Vec<_> = first.complement(&dat).iter(&dat).collect();
+	///
+	/// assert_eq!(rest, vec![
+	/// 	Address::new(0, 0, 1),
+	/// 	Address::new(0, 1, 0),
+	/// 	Address::new(0, 1, 1),
+	/// 	Address::new(0, 1, 2),
+	/// ]);
+	///
+	/// let complement = first.complement(&dat);
+	/// assert!(complement.contains(&Address::new(0, 0, 1)));
+	/// assert!(!complement.contains(&Address::new(0, 0, 2)));
+	/// assert!(!complement.contains(&Address::new(0, 0, 200)));
+	/// assert!(!complement.contains(&Address::new(1, 0, 0)));
+	/// ```
+	pub fn complement(&self, data: &Data) -> Selection {
+		let mut intervals = Vec::new();
+		for page in 0..data.layout.page_count() {
+			let line_count = data.line_count(&Reference::page_of(
+				&Address::new(page, 0, 0)));
+			for line in 0..line_count {
+				let column_count = data.column_count(&Reference::line_of(
+					&Address::new(page, line, 0)));
+				if column_count > 0 {
+					intervals.push(Interval::closed(
+						Address::new(page, line, 0),
+						Address::new(page, line, column_count - 1)));
+				}
+			}
+		}
+		Selection::new(intervals).difference(self)
+	}
+
+	/// Returns an iterator over the addresses in the selection which lie 
+	/// within the layout of the given `Data`, in order.
+	pub fn iter<'a>(&'a self, data: &'a Data) -> SelectionIter<'a> {
+		SelectionIter {
+			data: data,
+			ranges: self.ranges(),
+			index: 0,
+			next: None,
+		}
+	}
+
+	/// Returns the bounds of each interval in the selection, suitable for
+	/// range queries over ordered collections of addresses.
+	pub(crate) fn bounds(&self) 
+		-> Vec<(collections::Bound<Address>, collections::Bound<Address>)>
+	{
+		self.ranges()
+			.into_iter()
+			.map(|(start, end)| (
+				collections::Bound::Included(start), 
+				collections::Bound::Included(end)
+			))
+			.collect()
+	}
+
+	/// Creates a selection from sorted, disjoint, inclusive ranges.
+	fn from_ranges(ranges: Vec<(Address, Address)>) -> Selection {
+		Selection {
+			inner: ranges
+				.into_iter()
+				.map(|(start, end)| Interval::closed(start, end))
+				.collect()
+		}
+	}
+}
+
+
+/// Returns the inclusive start and end addresses of the given interval, or 
+/// `None` if it contains no addresses.
+fn closed_range(interval: &Interval<Address>) -> Option<(Address, Address)> {
+	let start = match interval.left_bound() {
+		interval::Bound::Include(address) => Some(address),
+		interval::Bound::Exclude(address) => address.successor(),
+	};
+	let end = match interval.right_bound() {
+		interval::Bound::Include(address) => Some(address),
+		interval::Bound::Exclude(address) => address.predecessor(),
+	};
+	match (start, end) {
+		(Some(start), Some(end)) if start <= end => Some((start, end)),
+		_ => None,
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SelectionIter
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the addresses in a `Selection` which lie within the layout
+/// of a `Data`.
+#[derive(Debug)]
+pub struct SelectionIter<'a> {
+	/// The `Data` providing the layout.
+	data: &'a Data,
+	/// The inclusive ranges of the selection.
+	ranges: Vec<(Address, Address)>,
+	/// The index of the current range.
+	index: usize,
+	/// The next candidate address in the current range.
+	next: Option<Address>,
+}
+
+
+impl<'a> Iterator for SelectionIter<'a> {
+	type Item = Address;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.index < self.ranges.len() {
+			let (start, end) = self.ranges[self.index];
+			let candidate = self.next.unwrap_or(start);
+			
+			match self.data.next_valid_address(candidate) {
+				Some(address) if address <= end => {
+					self.next = address.successor();
+					if self.next.is_none() {self.index = self.ranges.len();}
+					return Some(address);
+				},
+				_ => {
+					self.index += 1;
+					self.next = None;
+				},
+			}
+		}
+		None
+	}
+}
@@ -28,13 +28,15 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 // Local imports.
+use address::Address;
+use data::Data;
 use expression::Expression;
 
 // Non-local imports.
 use color::Color;
 
 // Standard imports.
-use std::cell::RefCell;
+use sync::Lock;
 use std::ops::{
 	Deref,
 	DerefMut,
@@ -46,10 +48,18 @@ use std::ops::{
 // Cell
 ////////////////////////////////////////////////////////////////////////////////
 /// A wrapper around a `Expression` for enabling interior mutability.
+///
+/// The `Color` generated by the `Expression` is cached when it is first
+/// requested. The cache must be invalidated whenever the expression or any of 
+/// its sources change. `Data::set_expression` does this for the cell and all of
+/// its dependents.
 #[derive(Debug, Clone)]
 pub struct Cell {
 	/// The `Expression` being wrapped.
-	expr: RefCell<Expression>,
+	expr: Lock<Expression>,
+
+	/// The cached result of the `Expression`.
+	cache: Lock<Cached>,
 }
 
 
@@ -57,20 +67,54 @@ impl Cell {
 	/// Creates a new `Cell` wrapping the given `Expression`.
 	pub fn new(element: Expression) -> Self {
 		Cell {
-			expr: RefCell::new(element),
+			expr: Lock::new(element),
+			cache: Lock::new(Cached::Invalid),
 		}
 	}
 
 	/// Returns the `Color` of the internal `Expression`, or `None` if it is 
-	/// invalid.
-	pub fn color(&self) -> Option<Color> {
-		self.expr.borrow().color()
+	/// invalid. Any references in the expression are resolved through the
+	/// given `Data`, relative to the `Cell`'s address. The result is cached 
+	/// until the cell is invalidated.
+	pub fn color(&self, data: &Data, address: Address) -> Option<Color> {
+		if let Cached::Valid(color) = *self.cache.borrow() {
+			return color;
+		}
+
+		let color = self.expr.borrow().color(data, address);
+		*self.cache.borrow_mut() = Cached::Valid(color);
+		color
+	}
+
+	/// Returns whether the `Cell`'s color is cached.
+	pub fn is_cached(&self) -> bool {
+		*self.cache.borrow() != Cached::Invalid
+	}
+
+	/// Discards the cached color, forcing it to be recomputed the next time it
+	/// is requested.
+	pub fn invalidate(&self) {
+		*self.cache.borrow_mut() = Cached::Invalid;
 	}
 }
 
 
+////////////////////////////////////////////////////////////////////////////////
+// Cached
+////////////////////////////////////////////////////////////////////////////////
+/// The cached result of evaluating a `Cell`'s `Expression`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Cached {
+	/// The `Expression` must be reevaluated.
+	Invalid,
+	/// The `Expression` evaluated to the given value.
+	Valid(Option<Color>),
+}
+
+
+
 impl Deref for Cell {
-	type Target = RefCell<Expression>;
+	type Target = Lock<Expression>;
 	fn deref(&self) -> &Self::Target {
 		&self.expr
 	}
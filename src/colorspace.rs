@@ -0,0 +1,1036 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides conversions between RGB `Color`s and the color spaces used by 
+//! color-expressions.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use utilities::{clamped, lerp_f32, lerp_u8_linear, nearly_equal};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::fmt;
+
+
+/// Converts an RGB channel octet to a value in the range [0, 1].
+#[inline]
+fn from_octet(octet: u8) -> f32 {
+	octet as f32 / 255.0
+}
+
+/// Converts a value in the range [0, 1] to an RGB channel octet. Values
+/// outside of the range are clamped.
+#[inline]
+fn to_octet(value: f32) -> u8 {
+	(clamped(value, 0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Returns the given hue normalized to the range [0, 360).
+#[inline]
+pub fn normalize_hue(hue: f32) -> f32 {
+	let hue = hue % 360.0;
+	if hue < 0.0 {hue + 360.0} else {hue}
+}
+
+/// Returns the hue of the given RGB components, along with the minimum and
+/// maximum component.
+fn hue_min_max(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+	let max = r.max(g).max(b);
+	let min = r.min(g).min(b);
+	let d = max - min;
+
+	let hue = if nearly_equal(d, 0.0) {
+		0.0
+	} else if nearly_equal(max, r) {
+		60.0 * ((g - b) / d)
+	} else if nearly_equal(max, g) {
+		60.0 * ((b - r) / d + 2.0)
+	} else {
+		60.0 * ((r - g) / d + 4.0)
+	};
+	(normalize_hue(hue), min, max)
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Hsl
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the HSL (hue, saturation, lightness) color space.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::colorspace::Hsl;
+/// use palette::utilities::nearly_equal;
+///
+/// let hsl = Hsl::from(Color::new(255, 0, 0));
+/// assert!(nearly_equal(hsl.hue, 0.0));
+/// assert!(nearly_equal(hsl.saturation, 1.0));
+/// assert!(nearly_equal(hsl.lightness, 0.5));
+///
+/// assert_eq!(Color::from(hsl), Color::new(255, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Hsl {
+	/// The hue of the color, in degrees. Lies in the range [0, 360).
+	pub hue: f32,
+
+	/// The saturation of the color. Lies in the range [0, 1].
+	pub saturation: f32,
+
+	/// The lightness of the color. Lies in the range [0, 1].
+	pub lightness: f32,
+}
+
+
+impl From<Color> for Hsl {
+	fn from(color: Color) -> Self {
+		let (r, g, b) = (
+			from_octet(color.red()),
+			from_octet(color.green()),
+			from_octet(color.blue()),
+		);
+		let (hue, min, max) = hue_min_max(r, g, b);
+		let lightness = (max + min) / 2.0;
+		let saturation = if nearly_equal(max, min) {
+			0.0
+		} else {
+			(max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+		};
+
+		Hsl {
+			hue: hue,
+			saturation: clamped(saturation, 0.0, 1.0),
+			lightness: lightness,
+		}
+	}
+}
+
+
+impl From<Hsl> for Color {
+	fn from(hsl: Hsl) -> Self {
+		let s = clamped(hsl.saturation, 0.0, 1.0);
+		let l = clamped(hsl.lightness, 0.0, 1.0);
+		let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+		let (r, g, b) = hue_chroma(hsl.hue, chroma);
+		let m = l - chroma / 2.0;
+		Color::new(to_octet(r + m), to_octet(g + m), to_octet(b + m))
+	}
+}
+
+
+/// Returns the RGB components for the given hue and chroma, before the
+/// lightness or value offset is applied.
+fn hue_chroma(hue: f32, chroma: f32) -> (f32, f32, f32) {
+	let h = normalize_hue(hue) / 60.0;
+	let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+
+	match h as u8 {
+		0 => (chroma, x, 0.0),
+		1 => (x, chroma, 0.0),
+		2 => (0.0, chroma, x),
+		3 => (0.0, x, chroma),
+		4 => (x, 0.0, chroma),
+		_ => (chroma, 0.0, x),
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Hsv
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the HSV (hue, saturation, value) color space.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::colorspace::Hsv;
+/// use palette::utilities::nearly_equal;
+///
+/// let hsv = Hsv::from(Color::new(0, 128, 0));
+/// assert!(nearly_equal(hsv.hue, 120.0));
+/// assert!(nearly_equal(hsv.saturation, 1.0));
+/// assert!(nearly_equal(hsv.value, 128.0 / 255.0));
+///
+/// assert_eq!(Color::from(hsv), Color::new(0, 128, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Hsv {
+	/// The hue of the color, in degrees. Lies in the range [0, 360).
+	pub hue: f32,
+
+	/// The saturation of the color. Lies in the range [0, 1].
+	pub saturation: f32,
+
+	/// The value of the color. Lies in the range [0, 1].
+	pub value: f32,
+}
+
+
+impl From<Color> for Hsv {
+	fn from(color: Color) -> Self {
+		let (r, g, b) = (
+			from_octet(color.red()),
+			from_octet(color.green()),
+			from_octet(color.blue()),
+		);
+		let (hue, min, max) = hue_min_max(r, g, b);
+		let saturation = if nearly_equal(max, 0.0) {
+			0.0
+		} else {
+			(max - min) / max
+		};
+
+		Hsv {
+			hue: hue,
+			saturation: clamped(saturation, 0.0, 1.0),
+			value: max,
+		}
+	}
+}
+
+
+impl From<Hsv> for Color {
+	fn from(hsv: Hsv) -> Self {
+		let s = clamped(hsv.saturation, 0.0, 1.0);
+		let v = clamped(hsv.value, 0.0, 1.0);
+		let chroma = v * s;
+		let (r, g, b) = hue_chroma(hsv.hue, chroma);
+		let m = v - chroma;
+		Color::new(to_octet(r + m), to_octet(g + m), to_octet(b + m))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// HueModel
+////////////////////////////////////////////////////////////////////////////////
+/// Selects a cylindrical color model in which to adjust colors.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum HueModel {
+	/// The HSL (hue, saturation, lightness) model.
+	Hsl,
+	/// The HSV (hue, saturation, value) model.
+	Hsv,
+}
+
+
+impl HueModel {
+	/// Returns the hue, saturation, and lightness or value components of the
+	/// given color in the model.
+	pub fn decompose(self, color: Color) -> (f32, f32, f32) {
+		match self {
+			HueModel::Hsl => {
+				let hsl = Hsl::from(color);
+				(hsl.hue, hsl.saturation, hsl.lightness)
+			},
+			HueModel::Hsv => {
+				let hsv = Hsv::from(color);
+				(hsv.hue, hsv.saturation, hsv.value)
+			},
+		}
+	}
+
+	/// Returns the color with the given hue, saturation, and lightness or 
+	/// value components in the model.
+	pub fn compose(self, hue: f32, saturation: f32, lightness: f32) -> Color {
+		match self {
+			HueModel::Hsl => Color::from(Hsl {
+				hue: hue,
+				saturation: saturation,
+				lightness: lightness,
+			}),
+			HueModel::Hsv => Color::from(Hsv {
+				hue: hue,
+				saturation: saturation,
+				value: lightness,
+			}),
+		}
+	}
+}
+
+
+impl Default for HueModel {
+	fn default() -> Self {
+		HueModel::Hsl
+	}
+}
+
+
+impl fmt::Display for HueModel {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HueModel::Hsl => write!(f, "hsl"),
+			HueModel::Hsv => write!(f, "hsv"),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// sRGB transfer functions
+////////////////////////////////////////////////////////////////////////////////
+/// Decodes a gamma-encoded sRGB component in the range [0, 1] into linear 
+/// light.
+#[inline]
+pub fn srgb_to_linear(value: f32) -> f32 {
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+/// Encodes a linear light component in the range [0, 1] into a gamma-encoded
+/// sRGB component.
+#[inline]
+pub fn linear_to_srgb(value: f32) -> f32 {
+	if value <= 0.003_130_8 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+/// Returns the linear light components of the given color.
+fn linear_rgb(color: Color) -> (f32, f32, f32) {
+	(
+		srgb_to_linear(from_octet(color.red())),
+		srgb_to_linear(from_octet(color.green())),
+		srgb_to_linear(from_octet(color.blue())),
+	)
+}
+
+/// Returns the color with the given linear light components.
+fn from_linear_rgb(r: f32, g: f32, b: f32) -> Color {
+	Color::new(
+		to_octet(linear_to_srgb(r)),
+		to_octet(linear_to_srgb(g)),
+		to_octet(linear_to_srgb(b)),
+	)
+}
+
+/// Returns the chroma and hue of the given rectangular color components.
+fn to_polar(a: f32, b: f32) -> (f32, f32) {
+	(a.hypot(b), normalize_hue(b.atan2(a).to_degrees()))
+}
+
+/// Returns the rectangular color components of the given chroma and hue.
+fn from_polar(chroma: f32, hue: f32) -> (f32, f32) {
+	let hue = hue.to_radians();
+	(chroma * hue.cos(), chroma * hue.sin())
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lab
+////////////////////////////////////////////////////////////////////////////////
+/// The D65 reference white, in CIE XYZ coordinates.
+const D65_WHITE: (f32, f32, f32) = (0.950_47, 1.0, 1.088_83);
+
+/// The CIELAB ratio of the linear and cube-root segments of the transfer 
+/// function.
+const LAB_EPSILON: f32 = 6.0 / 29.0;
+
+/// A color in the CIELAB color space, relative to the D65 white point.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::colorspace::{Lab, Lch};
+/// use palette::utilities::close;
+///
+/// // Reference values from http://www.brucelindbloom.com/ColorCalculator.html
+/// let lab = Lab::from(Color::new(255, 0, 0));
+/// assert!(close(lab.l, 53.2408, 0.01));
+/// assert!(close(lab.a, 80.0925, 0.01));
+/// assert!(close(lab.b, 67.2032, 0.01));
+///
+/// let lch = Lch::from(lab);
+/// assert!(close(lch.chroma, 104.5518, 0.01));
+/// assert!(close(lch.hue, 39.9990, 0.01));
+///
+/// let white = Lab::from(Color::new(255, 255, 255));
+/// assert!(close(white.l, 100.0, 0.01));
+/// assert!(close(white.a, 0.0, 0.01));
+/// assert!(close(white.b, 0.0, 0.01));
+///
+/// assert_eq!(Color::from(lab), Color::new(255, 0, 0));
+/// assert_eq!(Color::from(lch), Color::new(255, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Lab {
+	/// The lightness of the color. Lies in the range [0, 100].
+	pub l: f32,
+
+	/// The green-red axis of the color.
+	pub a: f32,
+
+	/// The blue-yellow axis of the color.
+	pub b: f32,
+}
+
+
+impl From<Color> for Lab {
+	fn from(color: Color) -> Self {
+		fn f(t: f32) -> f32 {
+			if t > LAB_EPSILON.powi(3) {
+				t.cbrt()
+			} else {
+				t / (3.0 * LAB_EPSILON.powi(2)) + 4.0 / 29.0
+			}
+		}
+
+		let (r, g, b) = linear_rgb(color);
+		let x = 0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b;
+		let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b;
+		let z = 0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b;
+
+		let fx = f(x / D65_WHITE.0);
+		let fy = f(y / D65_WHITE.1);
+		let fz = f(z / D65_WHITE.2);
+
+		Lab {
+			l: 116.0 * fy - 16.0,
+			a: 500.0 * (fx - fy),
+			b: 200.0 * (fy - fz),
+		}
+	}
+}
+
+
+impl From<Lab> for Color {
+	fn from(lab: Lab) -> Self {
+		fn f_inv(t: f32) -> f32 {
+			if t > LAB_EPSILON {
+				t.powi(3)
+			} else {
+				3.0 * LAB_EPSILON.powi(2) * (t - 4.0 / 29.0)
+			}
+		}
+
+		let fy = (lab.l + 16.0) / 116.0;
+		let x = D65_WHITE.0 * f_inv(fy + lab.a / 500.0);
+		let y = D65_WHITE.1 * f_inv(fy);
+		let z = D65_WHITE.2 * f_inv(fy - lab.b / 200.0);
+
+		from_linear_rgb(
+			 3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+			-0.969_266_0 * x + 1.876_010_8 * y + 0.041_556_0 * z,
+			 0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+		)
+	}
+}
+
+
+impl From<Lch> for Lab {
+	fn from(lch: Lch) -> Self {
+		let (a, b) = from_polar(lch.chroma, lch.hue);
+		Lab {
+			l: lch.l,
+			a: a,
+			b: b,
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lch
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the CIE LCh color space, the polar form of CIELAB.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Lch {
+	/// The lightness of the color. Lies in the range [0, 100].
+	pub l: f32,
+
+	/// The chroma of the color.
+	pub chroma: f32,
+
+	/// The hue of the color, in degrees. Lies in the range [0, 360).
+	pub hue: f32,
+}
+
+
+impl From<Lab> for Lch {
+	fn from(lab: Lab) -> Self {
+		let (chroma, hue) = to_polar(lab.a, lab.b);
+		Lch {
+			l: lab.l,
+			chroma: chroma,
+			hue: hue,
+		}
+	}
+}
+
+
+impl From<Color> for Lch {
+	fn from(color: Color) -> Self {
+		Lch::from(Lab::from(color))
+	}
+}
+
+
+impl From<Lch> for Color {
+	fn from(lch: Lch) -> Self {
+		Color::from(Lab::from(lch))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Oklab
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the Oklab color space.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::colorspace::{Oklab, Oklch};
+/// use palette::utilities::close;
+///
+/// // Reference values from https://bottosson.github.io/posts/oklab/
+/// let oklab = Oklab::from(Color::new(255, 0, 0));
+/// assert!(close(oklab.l, 0.62796, 0.0001));
+/// assert!(close(oklab.a, 0.22486, 0.0001));
+/// assert!(close(oklab.b, 0.12585, 0.0001));
+///
+/// let oklch = Oklch::from(oklab);
+/// assert!(close(oklch.chroma, 0.25768, 0.0001));
+/// assert!(close(oklch.hue, 29.2339, 0.01));
+///
+/// let white = Oklab::from(Color::new(255, 255, 255));
+/// assert!(close(white.l, 1.0, 0.0001));
+/// assert!(close(white.a, 0.0, 0.0001));
+/// assert!(close(white.b, 0.0, 0.0001));
+///
+/// assert_eq!(Color::from(oklab), Color::new(255, 0, 0));
+/// assert_eq!(Color::from(oklch), Color::new(255, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Oklab {
+	/// The lightness of the color. Lies in the range [0, 1].
+	pub l: f32,
+
+	/// The green-red axis of the color.
+	pub a: f32,
+
+	/// The blue-yellow axis of the color.
+	pub b: f32,
+}
+
+
+impl From<Color> for Oklab {
+	fn from(color: Color) -> Self {
+		let (r, g, b) = linear_rgb(color);
+		let l = (0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_99 * b).cbrt();
+		let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+		let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+		Oklab {
+			l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+			a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+			b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+		}
+	}
+}
+
+
+impl From<Oklab> for Color {
+	fn from(oklab: Oklab) -> Self {
+		let l = (oklab.l + 0.396_337_78 * oklab.a + 0.215_803_76 * oklab.b)
+			.powi(3);
+		let m = (oklab.l - 0.105_561_346 * oklab.a - 0.063_854_17 * oklab.b)
+			.powi(3);
+		let s = (oklab.l - 0.089_484_18 * oklab.a - 1.291_485_5 * oklab.b)
+			.powi(3);
+
+		from_linear_rgb(
+			4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+			-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+			-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+		)
+	}
+}
+
+
+impl From<Oklch> for Oklab {
+	fn from(oklch: Oklch) -> Self {
+		let (a, b) = from_polar(oklch.chroma, oklch.hue);
+		Oklab {
+			l: oklch.l,
+			a: a,
+			b: b,
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Oklch
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the Oklch color space, the polar form of Oklab.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Oklch {
+	/// The lightness of the color. Lies in the range [0, 1].
+	pub l: f32,
+
+	/// The chroma of the color.
+	pub chroma: f32,
+
+	/// The hue of the color, in degrees. Lies in the range [0, 360).
+	pub hue: f32,
+}
+
+
+impl From<Oklab> for Oklch {
+	fn from(oklab: Oklab) -> Self {
+		let (chroma, hue) = to_polar(oklab.a, oklab.b);
+		Oklch {
+			l: oklab.l,
+			chroma: chroma,
+			hue: hue,
+		}
+	}
+}
+
+
+impl From<Color> for Oklch {
+	fn from(color: Color) -> Self {
+		Oklch::from(Oklab::from(color))
+	}
+}
+
+
+impl From<Oklch> for Color {
+	fn from(oklch: Oklch) -> Self {
+		Color::from(Oklab::from(oklch))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// HueInterpolation
+////////////////////////////////////////////////////////////////////////////////
+/// Selects which way around the hue circle to interpolate hues.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum HueInterpolation {
+	/// Interpolate along the shorter arc between the hues.
+	Shorter,
+	/// Interpolate along the longer arc between the hues.
+	Longer,
+	/// Interpolate with increasing hue angles.
+	Increasing,
+	/// Interpolate with decreasing hue angles.
+	Decreasing,
+}
+
+
+impl HueInterpolation {
+	/// Returns the hue located at the ratio given by `amount` between the 
+	/// `start` and `end` hues.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::colorspace::HueInterpolation;
+	/// use palette::utilities::close;
+	///
+	/// let shorter = HueInterpolation::Shorter.interpolate(350.0, 30.0, 0.5);
+	/// let longer = HueInterpolation::Longer.interpolate(350.0, 30.0, 0.5);
+	/// let decreasing = HueInterpolation::Decreasing
+	/// 	.interpolate(350.0, 30.0, 0.25);
+	///
+	/// assert!(close(shorter, 10.0, 0.001));
+	/// assert!(close(longer, 190.0, 0.001));
+	/// assert!(close(decreasing, 270.0, 0.001));
+	/// ```
+	pub fn interpolate(self, start: f32, end: f32, amount: f32) -> f32 {
+		let mut start = normalize_hue(start);
+		let mut end = normalize_hue(end);
+		let delta = end - start;
+
+		match self {
+			HueInterpolation::Shorter => if delta > 180.0 {
+				start += 360.0;
+			} else if delta < -180.0 {
+				end += 360.0;
+			},
+
+			HueInterpolation::Longer => if delta > 0.0 && delta < 180.0 {
+				start += 360.0;
+			} else if delta > -180.0 && delta <= 0.0 {
+				end += 360.0;
+			},
+
+			HueInterpolation::Increasing => if end < start {
+				end += 360.0;
+			},
+
+			HueInterpolation::Decreasing => if start < end {
+				start += 360.0;
+			},
+		}
+		normalize_hue(start + (end - start) * clamped(amount, 0.0, 1.0))
+	}
+}
+
+
+impl Default for HueInterpolation {
+	fn default() -> Self {
+		HueInterpolation::Shorter
+	}
+}
+
+
+impl fmt::Display for HueInterpolation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HueInterpolation::Shorter		=> write!(f, "shorter"),
+			HueInterpolation::Longer		=> write!(f, "longer"),
+			HueInterpolation::Increasing	=> write!(f, "increasing"),
+			HueInterpolation::Decreasing	=> write!(f, "decreasing"),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ColorSpace
+////////////////////////////////////////////////////////////////////////////////
+/// Selects a color space in which to interpolate colors.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ColorSpace {
+	/// Interpolate the gamma-encoded RGB channels.
+	Rgb,
+	/// Interpolate the RGB channels in linear light.
+	LinearRgb,
+	/// Interpolate in CIELAB.
+	Lab,
+	/// Interpolate in CIE LCh, using the given hue interpolation.
+	Lch(HueInterpolation),
+	/// Interpolate in Oklab.
+	Oklab,
+	/// Interpolate in Oklch, using the given hue interpolation.
+	Oklch(HueInterpolation),
+}
+
+
+impl ColorSpace {
+	/// Returns the color located at the ratio given by `amount` between the 
+	/// `start` and `end` colors, interpolated in the color space.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::colorspace::{ColorSpace, HueInterpolation};
+	///
+	/// let red = Color::new(255, 0, 0);
+	/// let blue = Color::new(0, 0, 255);
+	///
+	/// assert_eq!(ColorSpace::Rgb.interpolate(red, blue, 0.5), 
+	/// 	Color::new(128, 0, 128));
+	/// assert_eq!(ColorSpace::LinearRgb.interpolate(red, blue, 0.5), 
+	/// 	Color::new(188, 0, 188));
+	/// assert_eq!(ColorSpace::Oklab.interpolate(red, blue, 0.0), red);
+	/// assert_eq!(
+	/// 	ColorSpace::Oklch(HueInterpolation::Shorter)
+	/// 		.interpolate(red, blue, 1.0), 
+	/// 	blue);
+	/// ```
+	pub fn interpolate(self, start: Color, end: Color, amount: f32) -> Color {
+		match self {
+			ColorSpace::Rgb => {
+				// Channels are rounded rather than truncated as by `lerp_u8`,
+				// so that blends agree with mixes of the same colors.
+				let lerp = |start: u8, end: u8| 
+					lerp_f32(start as f32, end as f32, amount).round() as u8;
+				Color::new(
+					lerp(start.red(), end.red()),
+					lerp(start.green(), end.green()),
+					lerp(start.blue(), end.blue()),
+				)
+			},
+
+			ColorSpace::LinearRgb => Color::new(
+				lerp_u8_linear(start.red(), end.red(), amount),
+				lerp_u8_linear(start.green(), end.green(), amount),
+				lerp_u8_linear(start.blue(), end.blue(), amount),
+			),
+
+			ColorSpace::Lab => {
+				let (s, e) = (Lab::from(start), Lab::from(end));
+				Color::from(Lab {
+					l: lerp_f32(s.l, e.l, amount),
+					a: lerp_f32(s.a, e.a, amount),
+					b: lerp_f32(s.b, e.b, amount),
+				})
+			},
+
+			ColorSpace::Lch(hue_interpolation) => {
+				let (s, e) = (Lch::from(start), Lch::from(end));
+				let (start_hue, end_hue) = powerless_hues(
+					(s.chroma, s.hue), 
+					(e.chroma, e.hue), 
+					LCH_ACHROMATIC_THRESHOLD);
+				Color::from(Lch {
+					l: lerp_f32(s.l, e.l, amount),
+					chroma: lerp_f32(s.chroma, e.chroma, amount),
+					hue: hue_interpolation
+						.interpolate(start_hue, end_hue, amount),
+				})
+			},
+
+			ColorSpace::Oklab => {
+				let (s, e) = (Oklab::from(start), Oklab::from(end));
+				Color::from(Oklab {
+					l: lerp_f32(s.l, e.l, amount),
+					a: lerp_f32(s.a, e.a, amount),
+					b: lerp_f32(s.b, e.b, amount),
+				})
+			},
+
+			ColorSpace::Oklch(hue_interpolation) => {
+				let (s, e) = (Oklch::from(start), Oklch::from(end));
+				let (start_hue, end_hue) = powerless_hues(
+					(s.chroma, s.hue), 
+					(e.chroma, e.hue), 
+					OKLCH_ACHROMATIC_THRESHOLD);
+				Color::from(Oklch {
+					l: lerp_f32(s.l, e.l, amount),
+					chroma: lerp_f32(s.chroma, e.chroma, amount),
+					hue: hue_interpolation
+						.interpolate(start_hue, end_hue, amount),
+				})
+			},
+		}
+	}
+
+	/// Returns the weighted average of the given colors and weights, averaged
+	/// in the color space. Colors with non-positive weights are ignored. In 
+	/// the polar color spaces, the hue is the circular mean of the hues of the
+	/// chromatic colors, so the result does not depend on the order of the 
+	/// colors. Returns `None` if no colors have a positive weight.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::colorspace::ColorSpace;
+	///
+	/// let colors = vec![
+	/// 	(Color::new(0, 0, 0), 1.0),
+	/// 	(Color::new(30, 60, 90), 1.0),
+	/// 	(Color::new(60, 120, 180), 1.0),
+	/// ];
+	/// assert_eq!(ColorSpace::Rgb.mix(colors), Some(Color::new(30, 60, 90)));
+	///
+	/// let colors = vec![
+	/// 	(Color::new(0, 0, 0), 1.0),
+	/// 	(Color::new(100, 100, 100), 3.0),
+	/// ];
+	/// assert_eq!(ColorSpace::Rgb.mix(colors), Some(Color::new(75, 75, 75)));
+	///
+	/// assert_eq!(ColorSpace::Oklab.mix(Vec::new()), None);
+	///
+	/// let (red, green) = (Color::new(255, 0, 0), Color::new(0, 255, 0));
+	/// let blue = Color::new(0, 0, 255);
+	/// assert_eq!(ColorSpace::Rgb.mix(vec![(red, 1.0), (blue, 1.0)]),
+	/// 	Some(ColorSpace::Rgb.interpolate(red, blue, 0.5)));
+	///
+	/// let oklch = ColorSpace::Oklch(Default::default());
+	/// assert_eq!(oklch.mix(vec![(red, 1.0), (green, 2.0), (blue, 1.0)]),
+	/// 	oklch.mix(vec![(blue, 1.0), (green, 2.0), (red, 1.0)]));
+	/// ```
+	pub fn mix<I>(self, colors: I) -> Option<Color>
+		where I: IntoIterator<Item=(Color, f32)>
+	{
+		let mut total = 0.0;
+		let mut sums = [0.0; 3];
+		// The weighted sum of the hue vectors of the chromatic colors.
+		let mut hue: Option<(f32, f32)> = None;
+
+		for (color, weight) in colors {
+			if weight <= 0.0 {continue;}
+			let components = self.components(color);
+			total += weight;
+			sums[0] += components[0] * weight;
+			sums[1] += components[1] * weight;
+
+			match self.achromatic_threshold() {
+				Some(threshold) => {
+					if components[1] < threshold {continue;}
+					let (x, y) = from_polar(weight, components[2]);
+					let (sum_x, sum_y) = hue.unwrap_or((0.0, 0.0));
+					hue = Some((sum_x + x, sum_y + y));
+				},
+				None => sums[2] += components[2] * weight,
+			}
+		}
+
+		if total <= 0.0 {return None;}
+		let third = match hue {
+			Some((x, y)) => to_polar(x, y).1,
+			None => sums[2] / total,
+		};
+		Some(self.compose(sums[0] / total, sums[1] / total, third))
+	}
+
+	/// Returns the chroma below which a color's hue is ignored if the color 
+	/// space is polar.
+	fn achromatic_threshold(self) -> Option<f32> {
+		match self {
+			ColorSpace::Lch(_) => Some(LCH_ACHROMATIC_THRESHOLD),
+			ColorSpace::Oklch(_) => Some(OKLCH_ACHROMATIC_THRESHOLD),
+			_ => None,
+		}
+	}
+
+	/// Returns the components of the given color in the color space.
+	fn components(self, color: Color) -> [f32; 3] {
+		match self {
+			ColorSpace::Rgb => [
+				color.red() as f32, 
+				color.green() as f32, 
+				color.blue() as f32,
+			],
+			ColorSpace::LinearRgb => {
+				let (r, g, b) = linear_rgb(color);
+				[r, g, b]
+			},
+			ColorSpace::Lab => {
+				let lab = Lab::from(color);
+				[lab.l, lab.a, lab.b]
+			},
+			ColorSpace::Lch(_) => {
+				let lch = Lch::from(color);
+				[lch.l, lch.chroma, lch.hue]
+			},
+			ColorSpace::Oklab => {
+				let oklab = Oklab::from(color);
+				[oklab.l, oklab.a, oklab.b]
+			},
+			ColorSpace::Oklch(_) => {
+				let oklch = Oklch::from(color);
+				[oklch.l, oklch.chroma, oklch.hue]
+			},
+		}
+	}
+
+	/// Returns the color with the given components in the color space.
+	fn compose(self, first: f32, second: f32, third: f32) -> Color {
+		match self {
+			ColorSpace::Rgb => Color::new(
+				clamped(first.round(), 0.0, 255.0) as u8,
+				clamped(second.round(), 0.0, 255.0) as u8,
+				clamped(third.round(), 0.0, 255.0) as u8,
+			),
+			ColorSpace::LinearRgb => from_linear_rgb(first, second, third),
+			ColorSpace::Lab => Color::from(Lab {l: first, a: second, b: third}),
+			ColorSpace::Lch(_) => Color::from(Lch {
+				l: first, 
+				chroma: second, 
+				hue: third,
+			}),
+			ColorSpace::Oklab => Color::from(Oklab {
+				l: first, 
+				a: second, 
+				b: third,
+			}),
+			ColorSpace::Oklch(_) => Color::from(Oklch {
+				l: first, 
+				chroma: second, 
+				hue: third,
+			}),
+		}
+	}
+}
+
+
+/// The chroma below which an LCh hue is considered meaningless.
+const LCH_ACHROMATIC_THRESHOLD: f32 = 0.01;
+
+/// The chroma below which an Oklch hue is considered meaningless.
+const OKLCH_ACHROMATIC_THRESHOLD: f32 = 0.000_1;
+
+/// Returns the hues to interpolate between for two polar colors given as 
+/// (chroma, hue) pairs. The hue of an achromatic color is meaningless, so it 
+/// is replaced by the hue of the other color.
+fn powerless_hues(start: (f32, f32), end: (f32, f32), threshold: f32) 
+	-> (f32, f32) 
+{
+	match (start.0 < threshold, end.0 < threshold) {
+		(true, false) => (end.1, end.1),
+		(false, true) => (start.1, start.1),
+		_ => (start.1, end.1),
+	}
+}
+
+
+impl Default for ColorSpace {
+	fn default() -> Self {
+		ColorSpace::Rgb
+	}
+}
+
+
+// Display `ColorSpace` as expression function options.
+impl fmt::Display for ColorSpace {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ColorSpace::Rgb => write!(f, "rgb"),
+			ColorSpace::LinearRgb => write!(f, "linear"),
+			ColorSpace::Lab => write!(f, "lab"),
+			ColorSpace::Oklab => write!(f, "oklab"),
+			ColorSpace::Lch(hue) => if hue == HueInterpolation::default() {
+				write!(f, "lch")
+			} else {
+				write!(f, "lch, {}", hue)
+			},
+			ColorSpace::Oklch(hue) => if hue == HueInterpolation::default() {
+				write!(f, "oklch")
+			} else {
+				write!(f, "oklch, {}", hue)
+			},
+		}
+	}
+}
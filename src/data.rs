@@ -29,40 +29,47 @@
 
 // Local imports.
 use address::{
+	is_name,
 	Address,
 	Reference,
 	Page, Line, Column, 
+	Selection,
 	PAGE_MAX, LINE_MAX, COLUMN_MAX,
 };
 use cell::Cell;
+use dependency::{Coverage, DependencyGraph, WildcardIndex};
 use expression::Expression;
+use layout::{GridLayout, Layout};
+use storage::{self, CellStorage, FreeSlots};
+use sync::Shared;
 use result::{
 	Error,
 	Result,
 };
 
+// Non-local imports.
+use color::Color;
+
 // Standard imports.
 use std::collections::{
-	BTreeMap,
 	BTreeSet,
+	Bound,
 	HashMap,
 };
-use std::rc::Rc;
+use std::borrow::Cow;
 use std::fmt;
 use std::mem;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::vec;
 
 
 
-/// Default function for `prepare_new_page` and `prepare_new_line` triggers.
-#[allow(unused_variables)]
-fn no_op(_: &mut Data, _: &Reference) {}
-
-
 ////////////////////////////////////////////////////////////////////////////////
 // MetaData
 ////////////////////////////////////////////////////////////////////////////////
 /// Provides metadata about palette data.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct MetaData {
 	/// A format-generated label for the item.
 	pub format_label: Option<String>,
@@ -70,11 +77,11 @@ pub struct MetaData {
 	/// A user-provided name for the item.
 	pub name: Option<String>,
 	
-	/// An override to the default line count for this group.
-	pub line_count: Line,
+	/// An override to the layout's line count for this group.
+	pub line_count: Option<Line>,
 	
-	/// An override to the default column count for this group.
-	pub column_count: Column,
+	/// An override to the layout's column count for this group.
+	pub column_count: Option<Column>,
 }
 
 impl fmt::Display for MetaData {
@@ -87,10 +94,13 @@ impl fmt::Display for MetaData {
 			_						  => Ok(())
 		}?;
 		
-		write!(f, " [Lines: {}] [Columns: {}]", 
-			self.line_count, 
-			self.column_count
-		)
+		if let Some(line_count) = self.line_count {
+			write!(f, " [Lines: {}]", line_count)?;
+		}
+		if let Some(column_count) = self.column_count {
+			write!(f, " [Columns: {}]", column_count)?;
+		}
+		Ok(())
 	}
 }
 
@@ -101,34 +111,44 @@ impl fmt::Display for MetaData {
 ////////////////////////////////////////////////////////////////////////////////
 /// Encapsulates a single palette's operation-relevant data.
 pub struct Data {
-	/// A map assigning addresses to `Palette` cells.
-	pub cells: BTreeMap<Address, Rc<Cell>>,
+	/// A map assigning addresses to `Palette` cells. Cells should only be 
+	/// added, changed, or removed using `Data` methods, so that cells shared
+	/// with a `Snapshot` are copied rather than changed.
+	pub cells: CellStorage,
 
 	/// A map assigning references to names.
 	pub names: HashMap<String, Reference>,
 
-	/// A map assigning metadata to references.
+	/// A map assigning metadata to references. Line and column counts should 
+	/// be changed using `set_line_count`, `set_column_count`, or 
+	/// `set_metadata`, so that free addresses are found correctly.
 	pub metadata: HashMap<Reference, MetaData>,
 
-	/// The maximum number of pages in the `Palette`.
-	pub maximum_page_count: Page,
+	/// The arrangement of the `Palette`'s pages, lines, and columns.
+	pub layout: Shared<Layout>,
 
-	/// The default number of lines in each page.
-	pub default_line_count: Line,
+	/// The dependencies between the `Palette`'s cells.
+	dependencies: DependencyGraph,
 
-	/// The default number of columns in each line.
-	pub default_column_count: Column,
+	/// The addresses of the cells whose expressions reference cells 
+	/// indirectly, indexed by the groups they reference. These must be 
+	/// relinked when new cells are created.
+	wildcard_dependents: WildcardIndex,
 
-	/// Called before a `Cell` is added to a new page in the palette. The 
-	/// expectation is that this will add the appropriate meta data to the 
-	/// palette. This will be called before the prepare_new_line function is 
-	/// called.
-	pub prepare_new_page: fn(&mut Data, &Reference),
-	
-	/// Called before an expression is added to a new line in the palette. The 
-	/// expectation is that this will add the appropriate meta data to the 
-	/// palette.
-	pub prepare_new_line: fn(&mut Data, &Reference),
+	/// An index of the addresses within the layout whose cells have colors, 
+	/// used to find free addresses. It is built when first needed, updated 
+	/// when cells are invalidated, and discarded when the line or column 
+	/// counts change.
+	free_slots: Option<FreeSlots>,
+
+	/// The number of times the `Data`'s cells have been shared with another 
+	/// `Data`, such as a `Snapshot`.
+	shares: AtomicUsize,
+
+	/// The addresses of the cells which are not shared with another `Data`, 
+	/// mapped to the value of `shares` when they were created or copied. Any 
+	/// other cell may be shared, and is copied before it is changed.
+	exclusive: HashMap<Address, usize>,
 }
 
 
@@ -144,8 +164,31 @@ impl Data {
 	}
 
 	/// Returns a reference to the cell located at the given address, or None if
-	/// the address is invalid or empty.
-	pub fn cell(&self, address: Address) -> Option<Rc<Cell>> {
+	/// the address is invalid or empty. The cell must not be changed through
+	/// the returned handle, as it may be shared with a `Snapshot`. If the 
+	/// `Data` changes the cell after a snapshot is taken, the cell is copied
+	/// and the handle will no longer refer to the `Data`'s cell.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// use palette::sync::Shared;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let address = Address::new(0, 0, 0);
+	/// let handle = dat.create_cell(address).unwrap();
+	/// let black = Expression::Color(Color::new(0, 0, 0));
+	/// dat.set_expression(address, black.clone()).unwrap();
+	/// assert!(Shared::ptr_eq(&handle, &dat.cell(address).unwrap()));
+	///
+	/// let snapshot = dat.snapshot();
+	/// dat.set_expression(address, black).unwrap();
+	/// assert!(Shared::ptr_eq(&handle, &snapshot.cell(address).unwrap()));
+	/// assert!(!Shared::ptr_eq(&handle, &dat.cell(address).unwrap()));
+	/// ```
+	pub fn cell(&self, address: Address) -> Option<Shared<Cell>> {
 		self.cells.get(&address).cloned()
 	}
 
@@ -165,13 +208,16 @@ impl Data {
 	/// 	.unwrap(); // Create empty `Cell` and unwrap it.
 	/// ```
 	#[cfg_attr(feature = "cargo-clippy", allow(map_entry))]
-	pub fn create_cell(&mut self, address: Address) -> Result<Rc<Cell>> {
+	pub fn create_cell(&mut self, address: Address) -> Result<Shared<Cell>> {
 		if self.cells.contains_key(&address) {
 			Err(Error::AddressInUse(address))
 		} else {
-			self.prepare_address(address)?;
-			let new_cell = Rc::new(Cell::new(Default::default()));
+			self.validate_address(address)?;
+			let new_cell = Shared::new(Cell::new(Default::default()));
 			self.cells.insert(address, new_cell.clone());
+			let shares = self.shares.load(Ordering::Relaxed);
+			self.exclusive.insert(address, shares);
+			self.link_wildcard_dependents(address);
 			Ok(new_cell)
 		}
 	}
@@ -184,14 +230,524 @@ impl Data {
 		let cell = self.cells
 			.remove(&address)
 			.ok_or_else(|| Error::EmptyAddress(address))?;
+		let shares = self.shares.load(Ordering::Relaxed);
+		let exclusive = self.exclusive.remove(&address) == Some(shares);
+		self.dependencies.unlink(address);
+		self.wildcard_dependents.remove(address);
+		self.invalidate(address);
 
-		// Extract Expression and discard wrappers.
-		let expr = mem::replace(&mut *cell.borrow_mut(), Default::default());
+		// Extract Expression and discard wrappers. A cell shared with a 
+		// `Snapshot` must be left unchanged.
+		let expr = if exclusive {
+			mem::replace(&mut *cell.borrow_mut(), Default::default())
+		} else {
+			cell.borrow().clone()
+		};
 		Ok(expr)
 	}
 
-	/// Returns the label associated with the given group, or
-	/// None if it has no label.
+	/// Stores the given expression in the cell at the given address, returning
+	/// the expression it replaces. The expressions of a `Data`'s cells should
+	/// only be replaced using this method, as it maintains the dependencies
+	/// between cells.
+	///
+	/// # Errors
+	///
+	/// Returns an `EmptyAddress` error if there is no cell at the given 
+	/// address, or a `CyclicReference` error if the cell would depend upon
+	/// itself.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color, Expression};
+	/// use palette::colorspace::ColorSpace;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+	/// dat.create_cell(a).unwrap();
+	/// dat.create_cell(b).unwrap();
+	///
+	/// let blend = Expression::Blend(b.into(), b.into(), 0.5, ColorSpace::Rgb);
+	/// dat.set_expression(a, blend).unwrap();
+	/// assert!(dat.set_expression(b, Expression::Reference(a.into())).is_err());
+	///
+	/// assert!(dat.dependents(b).contains(&a));
+	/// assert!(dat.transitive_sources(a).contains(&b));
+	/// ```
+	///
+	/// Changing a cell updates its dependents without recomputing any other
+	/// cells:
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color, Expression};
+	/// use palette::colorspace::ColorSpace;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let root = Address::new(0, 0, 0);
+	/// let copy = Address::new(0, 0, 1);
+	/// let blend = Address::new(0, 0, 2);
+	/// let other = Address::new(0, 1, 0);
+	/// for &address in &[root, copy, blend, other] {
+	/// 	dat.create_cell(address).unwrap();
+	/// }
+	///
+	/// let black = Expression::Color(Color::new(0, 0, 0));
+	/// dat.set_expression(root, black.clone()).unwrap();
+	/// dat.set_expression(other, black).unwrap();
+	/// dat.set_expression(copy, Expression::Reference(root.into())).unwrap();
+	/// dat.set_expression(blend, Expression::Blend(
+	/// 	root.into(), 
+	/// 	other.into(), 
+	/// 	0.5,
+	/// 	ColorSpace::Rgb,
+	/// )).unwrap();
+	///
+	/// assert_eq!(dat.color(blend), Some(Color::new(0, 0, 0)));
+	/// assert_eq!(dat.color(copy), Some(Color::new(0, 0, 0)));
+	/// 
+	/// // Edit the root color.
+	/// dat.set_expression(root, Expression::Color(Color::new(200, 100, 50)))
+	/// 	.unwrap();
+	/// assert!(!dat.cell(copy).unwrap().is_cached());
+	/// assert!(!dat.cell(blend).unwrap().is_cached());
+	/// assert!(dat.cell(other).unwrap().is_cached());
+	///
+	/// assert_eq!(dat.color(copy), Some(Color::new(200, 100, 50)));
+	/// assert_eq!(dat.color(blend), Some(Color::new(100, 50, 25)));
+	/// ```
+	pub fn set_expression(&mut self, address: Address, expression: Expression)
+		-> Result<Expression>
+	{
+		if !self.cells.contains_key(&address) {
+			return Err(Error::EmptyAddress(address));
+		}
+		self.check_expression(address, &expression)?;
+
+		let sources = self.resolve_sources(address, &expression);
+		self.dependencies.link(address, sources);
+		match self.wildcard_coverage(address, &expression) {
+			Some(coverage) 
+				=> self.wildcard_dependents.insert(address, coverage),
+			None => self.wildcard_dependents.remove(address),
+		}
+		let cell = self.cell_mut(address).expect("cell exists");
+		let old = mem::replace(&mut *cell.borrow_mut(), expression);
+		self.invalidate(address);
+		Ok(old)
+	}
+
+	/// Returns a `Snapshot` of the `Data`'s current cells, names, and metadata.
+	/// The snapshot shares its cells with the `Data` rather than copying them. 
+	/// See `Snapshot` for details.
+	pub fn snapshot(&self) -> Snapshot {
+		Snapshot {data: Shared::new(self.share())}
+	}
+
+	/// Returns a copy of the `Data` which shares its cells with the original.
+	/// Shared cells are copied by `cell_mut` before they are modified, so that
+	/// changes to either copy do not affect the other.
+	pub(crate) fn share(&self) -> Data {
+		// None of the cells are exclusive to either copy after this.
+		self.shares.fetch_add(1, Ordering::Relaxed);
+		Data {
+			cells: self.cells.clone(),
+			names: self.names.clone(),
+			metadata: self.metadata.clone(),
+			layout: self.layout.clone(),
+			dependencies: self.dependencies.clone(),
+			wildcard_dependents: self.wildcard_dependents.clone(),
+			free_slots: self.free_slots.clone(),
+			shares: AtomicUsize::new(0),
+			exclusive: HashMap::new(),
+		}
+	}
+
+	/// Returns the cell at the given address so that it may be modified, or 
+	/// `None` if there is no cell there. A cell which may be shared with 
+	/// another `Data` is first replaced by a copy of it.
+	fn cell_mut(&mut self, address: Address) -> Option<Shared<Cell>> {
+		let cell = self.cells.get(&address)?.clone();
+		let shares = self.shares.load(Ordering::Relaxed);
+		if self.exclusive.get(&address) == Some(&shares) {return Some(cell);}
+
+		let copy = Shared::new((*cell).clone());
+		self.cells.insert(address, copy.clone());
+		self.exclusive.insert(address, shares);
+		Some(copy)
+	}
+
+	/// Returns the color of the cell at the given address, or `None` if the
+	/// cell is empty or invalid.
+	pub fn color(&self, address: Address) -> Option<Color> {
+		self.cells.get(&address).and_then(|cell| cell.color(self, address))
+	}
+
+	/// Discards the cached colors of the cell at the given address and all of
+	/// the cells which depend upon it. Any of these cells which are shared with
+	/// a `Snapshot` are copied first, so that the snapshot keeps its colors.
+	pub fn invalidate(&mut self, address: Address) {
+		let dependents = self.dependencies.transitive_dependents(address);
+		for address in Some(address).into_iter().chain(dependents) {
+			if let Some(cell) = self.cell_mut(address) {
+				cell.invalidate();
+			}
+			// The cell's color may have changed, so it may now be free.
+			if let Some(ref mut free_slots) = self.free_slots {
+				free_slots.mark_stale(address);
+			}
+		}
+	}
+
+	/// Checks whether the given expression may be stored at the given address.
+	///
+	/// # Errors
+	///
+	/// Returns a `CyclicReference` error if the cell at the given address 
+	/// would depend upon itself. The cell is checked as if it exists, so an
+	/// expression whose references include its own address is rejected even
+	/// before the cell is created.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// use palette::operation::*;
+	/// 
+	/// let dat: Data = Default::default();
+	/// let address = Address::new(0, 0, 0);
+	/// assert!(dat.check_expression(address, &"mean(0:0:*)".parse().unwrap())
+	/// 	.is_err());
+	/// assert!(dat.check_expression(address, &"mean(0:1:*)".parse().unwrap())
+	/// 	.is_ok());
+	///
+	/// // Self-referencing inserts into free addresses leave no cell behind.
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// for text in &["mean(0:0:*)", "lighten(_:_:_, 10%)"] {
+	/// 	assert!(pal.apply(Box::new(InsertCell::new()
+	/// 		.with_expression(text.parse().unwrap())
+	/// 	)).is_err());
+	/// }
+	/// assert_eq!(pal.len(), 0);
+	/// ```
+	pub fn check_expression(&self, address: Address, expression: &Expression)
+		-> Result<()>
+	{
+		let sources = self.resolve_sources(address, expression);
+		if self.dependencies.would_cycle(address, &sources) {
+			Err(Error::CyclicReference(address))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Returns the addresses of the cells referenced by the given expression 
+	/// when it is stored at the given address.
+	fn resolve_sources(&self, address: Address, expression: &Expression) 
+		-> BTreeSet<Address> 
+	{
+		expression.references()
+			.into_iter()
+			.flat_map(|reference| match reference.address() {
+				Ok(source) => vec![source],
+				Err(_) => {
+					let mut sources = 
+						self.matching_addresses(reference, address);
+					// Include the address itself, even if it has no cell yet.
+					if reference.resolve(self, address)
+						.map_or(false, |selection| selection.contains(&address))
+					{
+						sources.push(address);
+					}
+					sources
+				},
+			})
+			.collect()
+	}
+
+	/// Returns the addresses of the existing cells referenced by the given 
+	/// `Reference`, in address order. The reference is resolved relative to
+	/// the given base address. Returns no addresses if the reference cannot be
+	/// resolved.
+	pub(crate) fn matching_addresses(&self, reference: &Reference, base: Address)
+		-> Vec<Address> 
+	{
+		let selection = match reference.resolve(self, base) {
+			Ok(selection) => selection,
+			Err(_) => return Vec::new(),
+		};
+
+		let mut addresses: Vec<_> = selection.bounds()
+			.into_iter()
+			.flat_map(|bounds| self.cells.range(bounds).map(|(a, _)| a))
+			.collect();
+		addresses.sort();
+		addresses.dedup();
+		addresses
+	}
+
+	/// Returns the coverage of the indirect references of the given expression
+	/// when it is stored at the given address, or `None` if it has no indirect
+	/// references. References which cannot be resolved have no coverage.
+	fn wildcard_coverage(&self, address: Address, expression: &Expression) 
+		-> Option<BTreeSet<Coverage>>
+	{
+		let references: Vec<_> = expression.references()
+			.into_iter()
+			.filter(|reference| reference.address().is_err())
+			.collect();
+		if references.is_empty() {return None;}
+
+		Some(references
+			.into_iter()
+			.filter_map(|reference| reference
+				.resolve_components(self, address)
+				.ok())
+			.collect())
+	}
+
+	/// Adds the newly created cell at the given address to the sources of any
+	/// cells whose expressions reference it indirectly. Only the cells whose 
+	/// references cover the address are resolved.
+	fn link_wildcard_dependents(&mut self, address: Address) {
+		let dependents: Vec<_> = self.wildcard_dependents
+			.covering(address)
+			.into_iter()
+			.filter(|&dependent| self.cells
+				.get(&dependent)
+				.map_or(false, |cell| cell.borrow()
+					.references()
+					.iter()
+					.any(|reference| reference
+						.resolve(self, dependent)
+						.map_or(false, |sel| sel.contains(&address)))))
+			.collect();
+
+		for dependent in dependents {
+			let mut sources = self.dependencies.sources(dependent);
+			sources.insert(address);
+			self.dependencies.link(dependent, sources);
+			self.invalidate(dependent);
+		}
+	}
+
+	/// Returns the addresses of the cells whose expressions directly reference 
+	/// the given address.
+	pub fn dependents(&self, address: Address) -> BTreeSet<Address> {
+		self.dependencies.dependents(address)
+	}
+
+	/// Returns the addresses of the cells whose expressions reference the 
+	/// given address, either directly or through other cells.
+	pub fn transitive_dependents(&self, address: Address) -> BTreeSet<Address> {
+		self.dependencies.transitive_dependents(address)
+	}
+
+	/// Returns the addresses directly referenced by the expression at the given
+	/// address.
+	pub fn sources(&self, address: Address) -> BTreeSet<Address> {
+		self.dependencies.sources(address)
+	}
+
+	/// Returns the addresses referenced by the expression at the given 
+	/// address, either directly or through other cells.
+	pub fn transitive_sources(&self, address: Address) -> BTreeSet<Address> {
+		self.dependencies.transitive_sources(address)
+	}
+
+	/// Returns the `Reference` defined for the given name, or `None` if the 
+	/// name is undefined.
+	pub fn named_reference(&self, name: &str) -> Option<&Reference> {
+		self.names.get(name)
+	}
+
+	/// Defines a name for the given `Reference`, replacing any previous 
+	/// definition. Returns the previous definition of the name. Cells whose
+	/// expressions use the name will be updated to use the new definition.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidName` error if the name is not a valid reference
+	/// name, a `CyclicName` error if the name would be defined in terms of 
+	/// itself, and a `CyclicReference` error if any cell would come to depend
+	/// upon itself. The names are unchanged if an error is returned.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color, Expression, Reference};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let (a, b, c) = (
+	/// 	Address::new(0, 0, 0), 
+	/// 	Address::new(0, 0, 1), 
+	/// 	Address::new(0, 0, 2));
+	/// for &(address, value) in &[(a, 10), (b, 20)] {
+	/// 	dat.create_cell(address).unwrap();
+	/// 	dat.set_expression(
+	/// 		address, 
+	/// 		Expression::Color(Color::new(value, value, value))
+	/// 	).unwrap();
+	/// }
+	/// dat.create_cell(c).unwrap();
+	/// dat.set_expression(c, "@skin".parse().unwrap()).unwrap();
+	/// assert_eq!(dat.color(c), None);
+	///
+	/// dat.define_name("skin", a.into()).unwrap();
+	/// assert_eq!(dat.color(c), Some(Color::new(10, 10, 10)));
+	///
+	/// dat.define_name("skin", b.into()).unwrap();
+	/// assert_eq!(dat.color(c), Some(Color::new(20, 20, 20)));
+	///
+	/// // Names which would make a cell depend on itself are rejected.
+	/// assert!(dat.define_name("skin", c.into()).is_err());
+	/// assert!(dat.define_name("skin", Reference::named("skin")).is_err());
+	/// assert_eq!(dat.named_reference("skin"), Some(&b.into()));
+	///
+	/// dat.rename_name("skin", "flesh").unwrap();
+	/// assert_eq!(dat.cell(c).unwrap().borrow().to_string(), "@flesh");
+	/// assert_eq!(dat.color(c), Some(Color::new(20, 20, 20)));
+	///
+	/// dat.remove_name("flesh").unwrap();
+	/// assert_eq!(dat.color(c), None);
+	/// ```
+	pub fn define_name<S>(&mut self, name: S, reference: Reference) 
+		-> Result<Option<Reference>>
+		where S: Into<String>
+	{
+		let name = name.into();
+		if !is_name(&name) {return Err(Error::InvalidName(name));}
+
+		let old = self.names.insert(name.clone(), reference);
+		let cyclic = match self.names[&name].resolve(self, Default::default()) {
+			Err(Error::CyclicName(cycle)) => Some(Error::CyclicName(cycle)),
+			_ => None,
+		};
+		let result = match cyclic {
+			Some(error) => Err(error),
+			None => self.relink_wildcard_dependents(),
+		};
+
+		if let Err(error) = result {
+			match old {
+				Some(old) => self.names.insert(name, old),
+				None => self.names.remove(&name),
+			};
+			return Err(error);
+		}
+		Ok(old)
+	}
+
+	/// Removes the definition of the given name. Returns the removed 
+	/// definition. Cells whose expressions use the name will no longer resolve
+	/// it.
+	///
+	/// # Errors
+	///
+	/// Returns an `UndefinedName` error if the name is not defined.
+	pub fn remove_name(&mut self, name: &str) -> Result<Reference> {
+		let old = self.names
+			.remove(name)
+			.ok_or_else(|| Error::UndefinedName(name.to_owned()))?;
+		self.relink_wildcard_dependents()?;
+		Ok(old)
+	}
+
+	/// Renames the given name, updating every expression and name definition 
+	/// which uses it.
+	///
+	/// # Errors
+	///
+	/// Returns an `UndefinedName` error if the old name is not defined, an 
+	/// `InvalidName` error if the new name is not a valid reference name, and
+	/// a `NameInUse` error if the new name is already defined or used.
+	pub fn rename_name(&mut self, old: &str, new: &str) -> Result<()> {
+		if !self.names.contains_key(old) {
+			return Err(Error::UndefinedName(old.to_owned()));
+		}
+		if !is_name(new) {return Err(Error::InvalidName(new.to_owned()));}
+		if self.uses_name(new) {return Err(Error::NameInUse(new.to_owned()));}
+
+		let reference = self.names.remove(old).expect("name is defined");
+		self.names.insert(new.to_owned(), reference);
+		for reference in self.names.values_mut() {
+			reference.rename(old, new);
+		}
+		let renamed: Vec<_> = self.cells
+			.iter()
+			.filter(|&(_, cell)| cell.borrow()
+				.references()
+				.iter()
+				.any(|reference| reference.uses_name(old)))
+			.map(|(address, _)| address)
+			.collect();
+		for address in renamed {
+			let cell = self.cell_mut(address).expect("cell exists");
+			for reference in cell.borrow_mut().references_mut() {
+				reference.rename(old, new);
+			}
+		}
+		self.relink_wildcard_dependents()
+	}
+
+	/// Returns whether the given name is defined or used by any expression or
+	/// name definition.
+	fn uses_name(&self, name: &str) -> bool {
+		self.names.contains_key(name) ||
+		self.names.values().any(|reference| reference.uses_name(name)) ||
+		self.cells.values().any(|cell| cell.borrow()
+			.references()
+			.iter()
+			.any(|reference| reference.uses_name(name)))
+	}
+
+	/// Recomputes the sources and coverage of every cell whose expression 
+	/// references other cells indirectly. This must be called whenever the 
+	/// names change.
+	///
+	/// # Errors
+	///
+	/// Returns a `CyclicReference` error if any cell would depend upon itself,
+	/// in which case the dependencies are unchanged.
+	fn relink_wildcard_dependents(&mut self) -> Result<()> {
+		let mut dependencies = self.dependencies.clone();
+		let dependents = self.wildcard_dependents.addresses();
+
+		let mut coverage = Vec::new();
+		for &address in &dependents {
+			if let Some(cell) = self.cells.get(&address) {
+				let expression = cell.borrow();
+				let sources = self.resolve_sources(address, &expression);
+				dependencies.link(address, sources);
+				coverage.push((
+					address, 
+					self.wildcard_coverage(address, &expression)
+						.unwrap_or_default()));
+			}
+		}
+		for &address in &dependents {
+			if dependencies.transitive_sources(address).contains(&address) {
+				return Err(Error::CyclicReference(address));
+			}
+		}
+
+		self.dependencies = dependencies;
+		for (address, coverage) in coverage {
+			self.wildcard_dependents.insert(address, coverage);
+		}
+		for &address in &dependents {
+			self.invalidate(address);
+		}
+		Ok(())
+	}
+
+	/// Returns the label associated with the given group, or None if it has no
+	/// label. Labels set in the metadata take precedence over the labels 
+	/// provided by the layout.
 	///
 	/// # Example
 	///
@@ -202,13 +758,23 @@ impl Data {
 	/// let mut dat: Data = Default::default();
 	/// dat.set_label(Reference::all(), "My Palette");
 	///
-	/// assert_eq!(dat.label(&Reference::all()), Some("My Palette"));
+	/// assert_eq!(dat.label(&Reference::all()).unwrap(), "My Palette");
 	/// ```
-	pub fn label(&self, group: &Reference) -> Option<&str> {
-		self.metadata
+	pub fn label(&self, group: &Reference) -> Option<Cow<str>> {
+		if let Some(label) = self.metadata
 			.get(group)
 			.and_then(|slotmap| slotmap.format_label.as_ref())
-			.map(|label| &label[..])
+		{
+			return Some(Cow::Borrowed(&label[..]));
+		}
+
+		if let Some((page, line)) = line_group(group) {
+			self.layout.line_label(page, line).map(Cow::Owned)
+		} else if let Some(page) = page_group(group) {
+			self.layout.page_label(page).map(Cow::Owned)
+		} else {
+			None
+		}
 	}
 
 	/// Sets the label for the given group.
@@ -253,99 +819,456 @@ impl Data {
 			.name = Some(name.into());
 	}
 
-	/// Returns the next free address after the given address. And error will be
-	/// returned if there are no more free addresses.
+	/// Returns the first address at or after the given address which has no 
+	/// color, wrapping around to the start of the palette's layout if needed. 
+	/// An error will be returned if there are no more free addresses.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// use palette::layout::GridLayout;
+	/// use palette::sync::Shared;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.layout = Shared::new(GridLayout::new(1, 2, 2));
+	/// let black = Expression::Color(Color::new(0, 0, 0));
+	/// for &(line, column) in &[(0, 1), (1, 0), (1, 1)] {
+	/// 	let address = Address::new(0, line, column);
+	/// 	dat.create_cell(address).unwrap();
+	/// 	dat.set_expression(address, black.clone()).unwrap();
+	/// }
+	///
+	/// assert_eq!(dat.first_free_address_after(Address::new(0, 0, 1)).unwrap(),
+	/// 	Address::new(0, 0, 0));
+	///
+	/// // A cell with no color is still free.
+	/// dat.create_cell(Address::new(0, 0, 0)).unwrap();
+	/// assert_eq!(dat.first_free_address_after(Address::new(0, 0, 1)).unwrap(),
+	/// 	Address::new(0, 0, 0));
+	///
+	/// dat.set_expression(Address::new(0, 0, 0), black).unwrap();
+	/// assert!(dat.first_free_address_after(Address::new(0, 0, 0)).is_err());
+	///
+	/// // Removing a cell frees the cells which depend upon it.
+	/// dat.set_expression(Address::new(0, 0, 1), 
+	/// 	Expression::Reference(Address::new(0, 1, 1).into())).unwrap();
+	/// dat.remove_cell(Address::new(0, 1, 1)).unwrap();
+	/// assert_eq!(dat.first_free_address_after(Address::new(0, 0, 0)).unwrap(),
+	/// 	Address::new(0, 0, 1));
+	/// ```
 	pub fn first_free_address_after(
 		&mut self, 
 		starting_address: Address) 
 		-> Result<Address> 
 	{
-		let mut address = starting_address;
-		self.prepare_address(address)?;
-
-		// Loop until we don't see a color.
-		while self.cells
-			.get(&address)
-			.and_then(|s| s.color())
-			.is_some() 
-		{
-			address = address.wrapping_step(
-				1,
-				self.maximum_page_count,
-				self.line_count(&Reference::page_of(&address)), 
-				self.column_count(&Reference::line_of(&address))
-			);
-			// Return an error if we've looped all the way around.
-			if address == starting_address {
-				return Err(Error::MaxCellLimitExceeded);
-			}
+		self.validate_address(starting_address)?;
+
+		let free_slots = self.take_free_slots();
+		let address = free_slots
+			.next_free(self, starting_address)
+			.or_else(|| self.next_valid_address(Address::default())
+				.and_then(|first| free_slots.next_free(self, first))
+				// Stop if we've looped all the way around.
+				.filter(|&address| address < starting_address));
+		self.free_slots = Some(free_slots);
+
+		address.ok_or(Error::MaxCellLimitExceeded)
+	}
+
+	/// Removes and returns the index of occupied addresses, building it first 
+	/// if it is missing or was built for a different layout, and refreshing 
+	/// its stale addresses otherwise.
+	fn take_free_slots(&mut self) -> FreeSlots {
+		match self.free_slots.take() {
+			Some(mut free_slots) if free_slots.is_current(self) => {
+				free_slots.refresh(self);
+				free_slots
+			},
+			_ => FreeSlots::new(self),
 		}
-		Ok(address)
 	}
 
-	/// Calls the prepare_new_page function and returns the current line count 
-	/// for the given group.
-	fn line_count(&mut self, group: &Reference) -> Line {
+	/// Returns the current line count for the given page group. This is the
+	/// line count set for the group, or the layout's line count for the page if
+	/// none is set.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// Format::Zpl.initialize(&mut dat);
+	/// let main = Reference::page_of(&Address::new(0, 0, 0));
+	///
+	/// assert_eq!(dat.line_count(&main), 14);
+	/// assert_eq!(dat.line_count(&Reference::page_of(&Address::new(1, 0, 0))),
+	/// 	16);
+	///
+	/// dat.set_line_count(main.clone(), 4).unwrap();
+	/// assert_eq!(dat.line_count(&main), 4);
+	/// ```
+	pub fn line_count(&self, group: &Reference) -> Line {
 		self.metadata
 			.get(group)
-			.map_or(self.default_line_count, |meta| meta.line_count)
+			.and_then(|meta| meta.line_count)
+			.or_else(|| group.page().ok().map(|page| 
+				self.layout.line_count(page)))
+			.unwrap_or(0)
 	}
 
-	/// Sets the line count for a group.
-	pub fn set_line_count(&mut self, group: Reference, line_count: Line) {
-		self.metadata
+	/// Sets the line count for a page group, returning the line count 
+	/// previously set for the group.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidGroup` error if the group is not a page group, an 
+	/// `EmptyGroup` error if the line count is zero, and a `StrandedCell` error
+	/// if a cell in the page would no longer lie within the layout.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let page = Reference::page_of(&Address::new(0, 0, 0));
+	/// dat.create_cell(Address::new(0, 5, 0)).unwrap();
+	///
+	/// assert!(dat.set_line_count(page.clone(), 0).is_err());
+	/// assert!(dat.set_line_count(page.clone(), 4).is_err());
+	/// assert!(dat.set_line_count(Reference::all(), 4).is_err());
+	///
+	/// assert_eq!(dat.set_line_count(page.clone(), 6).unwrap(), None);
+	/// assert_eq!(dat.line_count(&page), 6);
+	/// ```
+	pub fn set_line_count(&mut self, group: Reference, line_count: Line) 
+		-> Result<Option<Line>>
+	{
+		self.validate_line_count(&group, line_count)?;
+		self.free_slots = None;
+		Ok(self.metadata
 			.entry(group)
 			.or_insert_with(Default::default)
-			.line_count = line_count;
+			.line_count
+			.replace(line_count))
 	}
 
-	/// Calls the prepare_new_line function and returns the current column count 
-	/// for the given group.
-	fn column_count(&mut self, group: &Reference) -> Column {
+	/// Returns the current column count for the given line group. This is the
+	/// column count set for the group, or the layout's column count for the 
+	/// line if none is set.
+	pub fn column_count(&self, group: &Reference) -> Column {
 		self.metadata
 			.get(group)
-			.map_or(self.default_column_count, |meta| meta.column_count)
+			.and_then(|meta| meta.column_count)
+			.or_else(|| match (group.page(), group.line()) {
+				(Ok(page), Ok(line)) 
+					=> Some(self.layout.column_count(page, line)),
+				_ => None,
+			})
+			.unwrap_or(0)
 	}
 
-	/// Sets the column count for a group.
+	/// Sets the column count for a line group, returning the column count 
+	/// previously set for the group.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidGroup` error if the group is not a line group, an 
+	/// `EmptyGroup` error if the column count is zero, and a `StrandedCell` 
+	/// error if a cell in the line would no longer lie within the layout.
 	pub fn set_column_count(
 		&mut self, 
 		group: Reference, 
 		column_count: Column) 
+		-> Result<Option<Column>>
 	{
-		self.metadata
+		self.validate_column_count(&group, column_count)?;
+		self.free_slots = None;
+		Ok(self.metadata
 			.entry(group)
 			.or_insert_with(Default::default)
-			.column_count = column_count;
+			.column_count
+			.replace(column_count))
 	}
 
-	/// Returns whether the give address lies within the bounds defined by the 
-	/// wrapping and max page settings for the palette.
-	fn check_address(&mut self, address: Address) -> bool {
-		address.page < self.maximum_page_count &&
-		address.line < self.line_count(&Reference::page_of(&address)) &&
-		address.column < self.column_count(&Reference::line_of(&address))
+	/// Replaces the metadata for the given group, or removes it if `metadata` 
+	/// is `None`. Returns the replaced metadata.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the metadata's line or column count could not be 
+	/// set for the group using `set_line_count` or `set_column_count`.
+	pub fn set_metadata(&mut self, group: Reference, metadata: Option<MetaData>)
+		-> Result<Option<MetaData>>
+	{
+		if let Some(line_count) = metadata.as_ref()
+			.and_then(|metadata| metadata.line_count) 
+		{
+			self.validate_line_count(&group, line_count)?;
+		}
+		if let Some(column_count) = metadata.as_ref()
+			.and_then(|metadata| metadata.column_count) 
+		{
+			self.validate_column_count(&group, column_count)?;
+		}
+
+		self.free_slots = None;
+		Ok(match metadata {
+			Some(metadata) => self.metadata.insert(group, metadata),
+			None => self.metadata.remove(&group),
+		})
 	}
 
-	/// Prepares an address by calling the palette format's metadata functions.
-	/// This function must be called on any address that is first in a new line
-	/// in order to ensure the palette wraps properly.
-	fn prepare_address(&mut self, address: Address) -> Result<()> {
-		let default_line_count = self.default_line_count;
-		let default_column_count = self.default_column_count;
-		let page_group = Reference::page_of(&address);
-		let line_group = Reference::line_of(&address);
+	/// Checks whether the given line count may be set for the given group.
+	fn validate_line_count(&self, group: &Reference, line_count: Line) 
+		-> Result<()>
+	{
+		let page = page_group(group)
+			.ok_or_else(|| Error::InvalidGroup(group.clone()))?;
+		if line_count == 0 {
+			return Err(Error::EmptyGroup(group.clone()));
+		}
+		match self.stranded_cells(
+			Address::new(page, line_count, 0),
+			Address::new(page, LINE_MAX, COLUMN_MAX)).first()
+		{
+			Some(&address) => Err(Error::StrandedCell(address)),
+			None => Ok(()),
+		}
+	}
 
-		if !self.metadata.contains_key(&page_group) {
-			self.set_line_count(page_group.clone(), default_line_count);
-			(self.prepare_new_page)(self, &page_group);
+	/// Checks whether the given column count may be set for the given group.
+	fn validate_column_count(&self, group: &Reference, column_count: Column) 
+		-> Result<()>
+	{
+		let (page, line) = line_group(group)
+			.ok_or_else(|| Error::InvalidGroup(group.clone()))?;
+		if column_count == 0 {
+			return Err(Error::EmptyGroup(group.clone()));
+		}
+		match self.stranded_cells(
+			Address::new(page, line, column_count),
+			Address::new(page, line, COLUMN_MAX)).first()
+		{
+			Some(&address) => Err(Error::StrandedCell(address)),
+			None => Ok(()),
 		}
+	}
+
+	/// Returns the addresses of the cells between `start` and `end`, inclusive,
+	/// which lie within the palette's layout.
+	pub(crate) fn stranded_cells(&self, start: Address, end: Address) 
+		-> Vec<Address>
+	{
+		self.cells
+			.range(start..=end)
+			.map(|(address, _)| address)
+			.filter(|&address| self.check_address(address))
+			.collect()
+	}
+
+	/// Returns the first address at or after the given address which lies 
+	/// within the palette's layout, or `None` if there are no such addresses.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Reference};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.set_column_count(Reference::line_of(&Address::new(0, 0, 0)), 4)
+	/// 	.unwrap();
+	///
+	/// assert_eq!(dat.next_valid_address(Address::new(0, 0, 3)), 
+	/// 	Some(Address::new(0, 0, 3)));
+	/// assert_eq!(dat.next_valid_address(Address::new(0, 0, 4)), 
+	/// 	Some(Address::new(0, 1, 0)));
+	/// ```
+	pub fn next_valid_address(&self, address: Address) -> Option<Address> {
+		let mut address = address;
+		loop {
+			if address.page >= self.layout.page_count() {
+				return None;
+			}
 
-		if !self.metadata.contains_key(&line_group) {
-			self.set_column_count(line_group.clone(), default_column_count);
-			(self.prepare_new_line)(self, &line_group);
+			if address.line >= self.line_count(&Reference::page_of(&address)) {
+				if address.page == PAGE_MAX {return None;}
+				address = Address::new(address.page + 1, 0, 0);
+				continue;
+			}
+
+			if address.column >= 
+				self.column_count(&Reference::line_of(&address)) 
+			{
+				address = if address.line < LINE_MAX {
+					Address::new(address.page, address.line + 1, 0)
+				} else if address.page < PAGE_MAX {
+					Address::new(address.page + 1, 0, 0)
+				} else {
+					return None;
+				};
+				continue;
+			}
+
+			return Some(address);
 		}
-		
+	}
+
+	/// Returns the last address before the given address which lies within the
+	/// palette's layout, or `None` if there are no such addresses.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Reference};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.set_column_count(Reference::line_of(&Address::new(0, 0, 0)), 4)
+	/// 	.unwrap();
+	///
+	/// assert_eq!(dat.previous_valid_address(Address::new(0, 1, 0)), 
+	/// 	Some(Address::new(0, 0, 3)));
+	/// assert_eq!(dat.previous_valid_address(Address::new(0, 0, 0)), None);
+	/// ```
+	pub fn previous_valid_address(&self, address: Address) -> Option<Address> {
+		let mut address = address.predecessor()?;
+		loop {
+			if address.page >= self.layout.page_count() {
+				let last = self.layout.page_count().checked_sub(1)?;
+				address = Address::new(last, LINE_MAX, COLUMN_MAX);
+				continue;
+			}
+
+			let lines = self.line_count(&Reference::page_of(&address));
+			if address.line >= lines {
+				address = if lines > 0 {
+					Address::new(address.page, lines - 1, COLUMN_MAX)
+				} else {
+					Address::new(address.page, 0, 0).predecessor()?
+				};
+				continue;
+			}
+
+			let columns = self.column_count(&Reference::line_of(&address));
+			if address.column >= columns {
+				address = if columns > 0 {
+					Address::new(address.page, address.line, columns - 1)
+				} else {
+					Address::new(address.page, address.line, 0).predecessor()?
+				};
+				continue;
+			}
+
+			return Some(address);
+		}
+	}
+
+	/// Returns the flat color index of the given address. Indices count every
+	/// address in the palette's layout in address order, so that pages and 
+	/// lines with fewer lines or columns than others take up fewer indices.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidAddress` error if the address lies outside of the
+	/// palette's layout.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// Format::Zpl.initialize(&mut dat);
+	///
+	/// // The ZPL main page has 14 lines of 16 colors.
+	/// assert_eq!(dat.index_of(Address::new(0, 1, 2)).unwrap(), 18);
+	/// assert_eq!(dat.index_of(Address::new(1, 0, 0)).unwrap(), 224);
+	/// assert_eq!(dat.index_of(Address::new(2, 0, 3)).unwrap(), 483);
+	/// assert!(dat.index_of(Address::new(0, 14, 0)).is_err());
+	/// ```
+	pub fn index_of(&self, address: Address) -> Result<usize> {
+		self.validate_address(address)?;
+
+		let mut index = 0;
+		for page in 0..address.page {
+			for line in 0..self.line_count(&Reference::page_of(
+				&Address::new(page, 0, 0)))
+			{
+				index += self.line_size(page, line);
+			}
+		}
+		for line in 0..address.line {
+			index += self.line_size(address.page, line);
+		}
+		Ok(index + address.column as usize)
+	}
+
+	/// Returns the address with the given flat color index. This is the 
+	/// inverse of `index_of`.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidIndex` error if the index lies beyond the last 
+	/// address in the palette's layout.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// Format::Zpl.initialize(&mut dat);
+	///
+	/// assert_eq!(dat.address_of(18).unwrap(), Address::new(0, 1, 2));
+	/// assert_eq!(dat.address_of(223).unwrap(), Address::new(0, 13, 15));
+	/// assert_eq!(dat.address_of(224).unwrap(), Address::new(1, 0, 0));
+	///
+	/// for index in 0..1000 {
+	/// 	let address = dat.address_of(index).unwrap();
+	/// 	assert_eq!(dat.index_of(address).unwrap(), index);
+	/// }
+	/// ```
+	pub fn address_of(&self, index: usize) -> Result<Address> {
+		let mut remaining = index;
+		for page in 0..self.layout.page_count() {
+			for line in 0..self.line_count(&Reference::page_of(
+				&Address::new(page, 0, 0)))
+			{
+				let columns = self.line_size(page, line);
+				if remaining < columns {
+					return Ok(Address::new(page, line, remaining as Column));
+				}
+				remaining -= columns;
+			}
+		}
+		Err(Error::InvalidIndex(index))
+	}
+
+	/// Returns the number of columns in the given line.
+	fn line_size(&self, page: Page, line: Line) -> usize {
+		self.column_count(&Reference::line_of(&Address::new(page, line, 0))) 
+			as usize
+	}
+
+	/// Returns whether the give address lies within the palette's layout.
+	pub(crate) fn check_address(&self, address: Address) -> bool {
+		address.page < self.layout.page_count() &&
+		address.line < self.line_count(&Reference::page_of(&address)) &&
+		address.column < self.column_count(&Reference::line_of(&address))
+	}
+
+	/// Returns an `InvalidAddress` error if the given address lies outside of 
+	/// the palette's layout.
+	fn validate_address(&self, address: Address) -> Result<()> {
 		if self.check_address(address) {
 			Ok(())
 		} else {
@@ -355,9 +1278,9 @@ impl Data {
 
 	/// Retrieves n target addresses after starting_address from the palette. If 
 	/// overwrite is true, the addresses may potentially contain expressions. 
-	/// Otherwise, they will be empty. Addresses provided in the exclude list 
-	/// will be skipped. Returns an error if more targets are requested than are
-	/// available in the palette.
+	/// Otherwise, they will have no colors. Addresses provided in the exclude
+	/// list will be skipped. Returns an error if more targets are requested 
+	/// than are available in the palette.
 	pub fn find_targets(
 		&mut self, 
 		n: usize, 
@@ -366,47 +1289,59 @@ impl Data {
 		exclude: Option<Vec<Address>>)
 		-> Result<Vec<Address>>
 	{
+		let exclude: BTreeSet<Address> = exclude
+			.map(|exclude| exclude.into_iter().collect())
+			.unwrap_or_default();
 		let mut targets = BTreeSet::new();
 		let mut next = starting_address;
 
 		if overwrite { // Get overwrite block.
 			while targets.len() < n {
-				self.prepare_address(next)?;
+				self.validate_address(next)?;
 				if targets.contains(&next) {
 					return Err(Error::MaxCellLimitExceeded);
 				}
 				// Add the target if it's not in the exclude list.
-				if !exclude.clone().map_or(false, |ex| ex.contains(&next)) {
+				if !exclude.contains(&next) {
 					targets.insert(next);
 				}
 				next = next.wrapping_step(
 					1,
-					self.maximum_page_count,
+					self.layout.page_count(),
 					self.line_count(&Reference::page_of(&next)),
 					self.column_count(&Reference::line_of(&next)),
 				);
 			}
 		} else { // Find n free addresses.
-			self.prepare_address(next)?;
+			self.validate_address(next)?;
+
+			// The first free address found. Finding it again means we've 
+			// looped all the way around.
+			let mut first_free = None;
 
 			// Check if the starting address is empty.
-			if next == starting_address && 
-				self.cells.get(&next).and_then(|s| s.color()).is_none() &&
-				!exclude.clone().map_or(false, |ex| ex.contains(&next))
-			{
-				targets.insert(next);
+			if self.color(next).is_none() {
+				first_free = Some(next);
+				if !exclude.contains(&next) {
+					targets.insert(next);
+				}
 			}
 			
 			while targets.len() < n {
 				next = next.wrapping_step(
 					1,
-					self.maximum_page_count,
+					self.layout.page_count(),
 					self.line_count(&Reference::page_of(&next)),
 					self.column_count(&Reference::line_of(&next)),
 				);
 				next = self.first_free_address_after(next)?;
+				if first_free == Some(next) {
+					return Err(Error::MaxCellLimitExceeded);
+				}
+				first_free = first_free.or(Some(next));
+
 				// Add the target if it's not in the exclude list.
-				if !exclude.clone().map_or(false, |ex| ex.contains(&next)) {
+				if !exclude.contains(&next) {
 					targets.insert(next);
 				}
 			}
@@ -422,78 +1357,593 @@ impl fmt::Debug for Data {
 		write!(f, "Data {{ \
 			cells: {:#?}, \
 			names: {:#?}, \
-			maximum_page_count: {}, \
-			default_line_count: {}, \
-			default_column_count: {}",
+			layout: {:?}",
 			self.cells,
 			self.names,
-			self.maximum_page_count,
-			self.default_line_count,
-			self.default_column_count,
+			self.layout,
 		)
 	}
 }
 
 
+impl Data {
+	/// Returns the `MetaData` of the given group, with its label, line count, 
+	/// and column count resolved from the layout when they aren't set on the 
+	/// group.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// Format::Zpl.initialize(&mut dat);
+	///
+	/// let main = dat.resolved_metadata(
+	/// 	&Reference::page_of(&Address::new(0, 0, 0)));
+	/// assert_eq!(main.name, Some("Main".into()));
+	/// assert_eq!(main.format_label, Some("Level 0".into()));
+	/// assert_eq!(main.line_count, Some(14));
+	/// assert_eq!(main.column_count, None);
+	/// ```
+	pub fn resolved_metadata(&self, group: &Reference) -> MetaData {
+		let mut metadata = self.metadata
+			.get(group)
+			.cloned()
+			.unwrap_or_default();
+		metadata.format_label = self.label(group).map(Cow::into_owned);
+
+		if line_group(group).is_some() {
+			metadata.column_count = Some(self.column_count(group));
+		} else if page_group(group).is_some() {
+			metadata.line_count = Some(self.line_count(group));
+		}
+		metadata
+	}
+
+	/// Returns an iterator over the pages containing cells, in order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// Format::Zpl.initialize(&mut dat);
+	/// for &address in &[
+	/// 	Address::new(0, 0, 0),
+	/// 	Address::new(0, 0, 1),
+	/// 	Address::new(0, 2, 0),
+	/// 	Address::new(3, 1, 4),
+	/// ] {
+	/// 	dat.create_cell(address).unwrap();
+	/// 	let expression = Expression::Color(Color::new(1, 2, 3));
+	/// 	dat.set_expression(address, expression).unwrap();
+	/// }
+	///
+	/// let pages: Vec<_> = dat.pages().map(|page| page.page).collect();
+	/// assert_eq!(pages, vec![0, 3]);
+	///
+	/// let main = dat.pages().next().unwrap();
+	/// assert_eq!(main.metadata.name, Some("Main".into()));
+	///
+	/// let lines: Vec<_> = main.lines().map(|line| line.line).collect();
+	/// assert_eq!(lines, vec![0, 2]);
+	///
+	/// let first = main.lines().next().unwrap();
+	/// assert_eq!(first.metadata.column_count, Some(16));
+	///
+	/// let cells: Vec<_> = first.cells()
+	/// 	.map(|entry| (entry.address, entry.color))
+	/// 	.collect();
+	/// assert_eq!(cells, vec![
+	/// 	(Address::new(0, 0, 0), Some(Color::new(1, 2, 3))),
+	/// 	(Address::new(0, 0, 1), Some(Color::new(1, 2, 3))),
+	/// ]);
+	/// ```
+	pub fn pages(&self) -> Pages {
+		Pages {
+			data: self,
+			next: Some(0),
+		}
+	}
+
+	/// Returns an iterator over the lines of the given page containing cells, 
+	/// in order.
+	pub fn lines(&self, page: Page) -> Lines {
+		Lines {
+			data: self,
+			page: page,
+			next: Some(0),
+		}
+	}
+
+	/// Returns an iterator over all of the cells, in address order.
+	pub fn iter(&self) -> Cells {
+		Cells::new(self, vec![(Bound::Unbounded, Bound::Unbounded)])
+	}
+
+	/// Returns an iterator over the cells whose addresses lie in the given 
+	/// `Selection`, in address order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::address::Selection;
+	/// use palette::data::Data;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// for column in 0..8 {
+	/// 	dat.create_cell(Address::new(0, 0, column)).unwrap();
+	/// }
+	///
+	/// let selection = Selection::range(
+	/// 	Address::new(0, 0, 2), 
+	/// 	Address::new(0, 0, 4));
+	/// let selected: Vec<_> = dat.select(&selection)
+	/// 	.map(|entry| entry.address)
+	/// 	.collect();
+	/// assert_eq!(selected, vec![
+	/// 	Address::new(0, 0, 2),
+	/// 	Address::new(0, 0, 3),
+	/// 	Address::new(0, 0, 4),
+	/// ]);
+	/// ```
+	pub fn select(&self, selection: &Selection) -> Cells {
+		Cells::new(self, selection.bounds())
+	}
+
+	/// Returns an iterator over the addresses within the layout which contain 
+	/// no cell, in order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::Data;
+	/// use palette::layout::GridLayout;
+	/// use palette::sync::Shared;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.layout = Shared::new(GridLayout::new(1, 2, 2));
+	/// dat.create_cell(Address::new(0, 0, 1)).unwrap();
+	///
+	/// let empty: Vec<_> = dat.empty_slots().collect();
+	/// assert_eq!(empty, vec![
+	/// 	Address::new(0, 0, 0),
+	/// 	Address::new(0, 1, 0),
+	/// 	Address::new(0, 1, 1),
+	/// ]);
+	/// ```
+	pub fn empty_slots(&self) -> EmptySlots {
+		EmptySlots {
+			data: self,
+			next: Some(Address::default()),
+		}
+	}
+}
+
+
+/// Returns the page of the given group, or `None` if it is not a page group.
+pub(crate) fn page_group(group: &Reference) -> Option<Page> {
+	group.page()
+		.ok()
+		.filter(|&page| *group == Reference::page_of(&Address::new(page, 0, 0)))
+}
+
+
+/// Returns the page and line of the given group, or `None` if it is not a line
+/// group.
+pub(crate) fn line_group(group: &Reference) -> Option<(Page, Line)> {
+	match (group.page(), group.line()) {
+		(Ok(page), Ok(line)) 
+			if *group == Reference::line_of(&Address::new(page, line, 0))
+			=> Some((page, line)),
+		_ => None,
+	}
+}
+
+
+/// Returns the name and label of the given metadata for display, or `None` if 
+/// it has neither.
+fn title(metadata: &MetaData) -> Option<String> {
+	match (metadata.name.as_ref(), metadata.format_label.as_ref()) {
+		(Some(name), Some(label))
+			=> Some(format!("\"{}\" ({})", name, label)),
+		(None, Some(label))
+			=> Some(format!("({})", label)),
+		(Some(name), None)
+			=> Some(format!("\"{}\"", name)),
+		_	=> None,
+	}
+}
+
+
 impl fmt::Display for Data {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		if let Some(data) = self.metadata.get(&Reference::all()) {
 			write!(f, "{} ", data)?;
 		}
 		write!(f, 
-			"[{} pages] [{} expression(s)] [default wrap {}:{}]\n",
-			self.maximum_page_count,
-			self.len(),
-			self.default_line_count,
-			self.default_column_count
+			"[{} pages] [{} expression(s)]\n",
+			self.layout.page_count(),
+			self.len()
 		)?;
 
-		let mut cur_page_group = Reference::all();
-		let mut cur_line_group = Reference::all();
-		for (&address, cell) in &self.cells {
-
-			if cur_page_group != Reference::page_of(&address) {
-				match self.metadata.get(&Reference::page_of(&address)) {
-					Some(meta) => writeln!(f, "Page {} - {}", 
-						Reference::page_of(&address), 
-						meta)?
-					,
-					None => writeln!(f, "Page {}", Reference::page_of(&address))?,
-				}
-			};
+		for page in self.pages() {
+			match title(&page.metadata) {
+				Some(title) 
+					=> write!(f, "Page {} - {}", page.group(), title)?,
+				None => write!(f, "Page {}", page.group())?,
+			}
+			writeln!(f, " [Lines: {}]", 
+				page.metadata.line_count.unwrap_or(0))?;
 
-			cur_page_group = Reference::page_of(&address);
-			if cur_line_group != Reference::line_of(&address) {
-				if let Some(meta) = self.metadata.get(&Reference::line_of(&address)) {
-					write!(f, "\t{}\n", meta)?;
+			for line in page.lines() {
+				if let Some(title) = title(&line.metadata) {
+					write!(f, "\t{}", title)?;
 				}
-				cur_line_group = Reference::line_of(&address);
+				writeln!(f, "\t[Columns: {}]", 
+					line.metadata.column_count.unwrap_or(0))?;
 				write!(f, "\tAddress   Color\n")?;
-			}
 
-			writeln!(f, "\t{:X}  {}",
-				address,
-				cell.borrow()
-					.color()
-					.map(|c| c.to_string())
-					.unwrap_or("-".to_string()))?;
+				for entry in line.cells() {
+					writeln!(f, "\t{:X}  {}",
+						entry.address,
+						entry.color
+							.map(|c| c.to_string())
+							.unwrap_or("-".to_string()))?;
+				}
+			}
 		}
 		Ok(())
 	}
 }
 
 
+// Clones the `Data` deeply, so that the cells of the clone can be modified 
+// without affecting the original.
+impl Clone for Data {
+	fn clone(&self) -> Self {
+		Data {
+			cells: self.cells.deep_clone(),
+			names: self.names.clone(),
+			metadata: self.metadata.clone(),
+			layout: self.layout.clone(),
+			dependencies: self.dependencies.clone(),
+			wildcard_dependents: self.wildcard_dependents.clone(),
+			free_slots: self.free_slots.clone(),
+			shares: AtomicUsize::new(0),
+			exclusive: self.cells.keys().map(|address| (address, 0)).collect(),
+		}
+	}
+}
+
+
 impl Default for Data {
 	fn default() -> Self {
 		Data {
-			cells: BTreeMap::new(),
+			cells: CellStorage::default(),
 			names: HashMap::new(),
 			metadata: HashMap::new(),
-			maximum_page_count: PAGE_MAX,
-			default_line_count: LINE_MAX,
-			default_column_count: COLUMN_MAX,
-			prepare_new_page: no_op,
-			prepare_new_line: no_op,
+			layout: Shared::new(GridLayout::default()),
+			dependencies: DependencyGraph::new(),
+			wildcard_dependents: WildcardIndex::default(),
+			free_slots: None,
+			shares: AtomicUsize::new(0),
+			exclusive: HashMap::new(),
 		}
 	}
-}
\ No newline at end of file
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Snapshot
+////////////////////////////////////////////////////////////////////////////////
+/// An immutable copy of a `Data` at some point in time, including its cells,
+/// names, and metadata. Snapshots are taken with `Data::snapshot` and may be 
+/// cloned and stored cheaply, as every clone shares the same copy. Snapshots 
+/// dereference to `Data`, so any non-mutating `Data` method may be used on 
+/// them.
+///
+/// Taking a snapshot copies the maps holding the `Data`'s cells, names, 
+/// metadata, and dependencies, but not the cells themselves. The cells are 
+/// shared until the `Data` changes one of them, at which point that cell is 
+/// copied. For this to work, cells must only be changed through `Data` 
+/// methods such as `set_expression`, and never through their handles.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::Data;
+/// use palette::sync::Shared;
+/// 
+/// let mut dat: Data = Default::default();
+/// let (address, other) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+/// dat.create_cell(address).unwrap();
+/// dat.set_expression(address, Expression::Color(Color::new(10, 20, 30)))
+/// 	.unwrap();
+/// dat.create_cell(other).unwrap();
+/// dat.names.insert("base".into(), address.into());
+///
+/// let before = dat.snapshot();
+/// dat.set_expression(address, Expression::Color(Color::new(40, 50, 60)))
+/// 	.unwrap();
+/// dat.names.clear();
+///
+/// assert_eq!(before.color(address), Some(Color::new(10, 20, 30)));
+/// assert_eq!(before.named_reference("base"), Some(&address.into()));
+/// assert_eq!(dat.color(address), Some(Color::new(40, 50, 60)));
+///
+/// // Only the changed cell was copied.
+/// assert!(!Shared::ptr_eq(&before.cell(address).unwrap(), 
+/// 	&dat.cell(address).unwrap()));
+/// assert!(Shared::ptr_eq(&before.cell(other).unwrap(), 
+/// 	&dat.cell(other).unwrap()));
+///
+/// // Restore the snapshot.
+/// dat = before.to_data();
+/// assert_eq!(dat.color(address), Some(Color::new(10, 20, 30)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+	/// The copied data.
+	data: Shared<Data>,
+}
+
+
+impl Snapshot {
+	/// Returns a mutable copy of the snapshot's `Data`. The copy is a deep
+	/// clone, so its cells are not shared with the snapshot.
+	pub fn to_data(&self) -> Data {
+		(*self.data).clone()
+	}
+}
+
+
+impl Deref for Snapshot {
+	type Target = Data;
+
+	fn deref(&self) -> &Data {
+		&self.data
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Pages
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the pages of a `Data` which contain cells.
+#[derive(Debug, Clone)]
+pub struct Pages<'a> {
+	/// The `Data` being iterated over.
+	data: &'a Data,
+	/// The first page to search for cells.
+	next: Option<Page>,
+}
+
+
+impl<'a> Iterator for Pages<'a> {
+	type Item = PageEntry<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let start = Address::new(self.next?, 0, 0);
+		let page = match self.data.cells.range(start..).next() {
+			Some((address, _)) => address.page,
+			None => {
+				self.next = None;
+				return None;
+			},
+		};
+
+		self.next = page.checked_add(1);
+		Some(PageEntry {
+			page: page,
+			metadata: self.data.resolved_metadata(
+				&Reference::page_of(&Address::new(page, 0, 0))),
+			data: self.data,
+		})
+	}
+}
+
+
+/// A page of a `Data` and its resolved `MetaData`.
+#[derive(Debug, Clone)]
+pub struct PageEntry<'a> {
+	/// The page.
+	pub page: Page,
+	/// The resolved `MetaData` of the page.
+	pub metadata: MetaData,
+	/// The `Data` containing the page.
+	data: &'a Data,
+}
+
+
+impl<'a> PageEntry<'a> {
+	/// Returns the group `Reference` of the page.
+	pub fn group(&self) -> Reference {
+		Reference::page_of(&Address::new(self.page, 0, 0))
+	}
+
+	/// Returns an iterator over the lines of the page containing cells.
+	pub fn lines(&self) -> Lines<'a> {
+		self.data.lines(self.page)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lines
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the lines of a page which contain cells.
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+	/// The `Data` being iterated over.
+	data: &'a Data,
+	/// The page containing the lines.
+	page: Page,
+	/// The first line to search for cells.
+	next: Option<Line>,
+}
+
+
+impl<'a> Iterator for Lines<'a> {
+	type Item = LineEntry<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let start = Address::new(self.page, self.next?, 0);
+		let end = Address::new(self.page, LINE_MAX, COLUMN_MAX);
+		let line = match self.data.cells.range(start..=end).next() {
+			Some((address, _)) => address.line,
+			None => {
+				self.next = None;
+				return None;
+			},
+		};
+
+		self.next = line.checked_add(1);
+		Some(LineEntry {
+			page: self.page,
+			line: line,
+			metadata: self.data.resolved_metadata(
+				&Reference::line_of(&Address::new(self.page, line, 0))),
+			data: self.data,
+		})
+	}
+}
+
+
+/// A line of a `Data` and its resolved `MetaData`.
+#[derive(Debug, Clone)]
+pub struct LineEntry<'a> {
+	/// The page containing the line.
+	pub page: Page,
+	/// The line.
+	pub line: Line,
+	/// The resolved `MetaData` of the line.
+	pub metadata: MetaData,
+	/// The `Data` containing the line.
+	data: &'a Data,
+}
+
+
+impl<'a> LineEntry<'a> {
+	/// Returns the group `Reference` of the line.
+	pub fn group(&self) -> Reference {
+		Reference::line_of(&Address::new(self.page, self.line, 0))
+	}
+
+	/// Returns an iterator over the cells of the line.
+	pub fn cells(&self) -> Cells<'a> {
+		let start = Address::new(self.page, self.line, 0);
+		let end = Address::new(self.page, self.line, COLUMN_MAX);
+		Cells::new(
+			self.data, 
+			vec![(Bound::Included(start), Bound::Included(end))])
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Cells
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the cells of a `Data` within a collection of address 
+/// ranges.
+#[derive(Debug)]
+pub struct Cells<'a> {
+	/// The `Data` being iterated over.
+	data: &'a Data,
+	/// The remaining address ranges.
+	bounds: vec::IntoIter<(Bound<Address>, Bound<Address>)>,
+	/// The cells in the current address range.
+	current: Option<storage::Iter<'a>>,
+}
+
+
+impl<'a> Cells<'a> {
+	/// Creates a new iterator over the cells within the given bounds.
+	fn new(data: &'a Data, bounds: Vec<(Bound<Address>, Bound<Address>)>) 
+		-> Cells<'a>
+	{
+		Cells {
+			data: data,
+			bounds: bounds.into_iter(),
+			current: None,
+		}
+	}
+}
+
+
+impl<'a> Iterator for Cells<'a> {
+	type Item = CellEntry<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some((address, cell)) = self.current
+				.as_mut()
+				.and_then(|range| range.next())
+			{
+				return Some(CellEntry {
+					address: address,
+					cell: cell,
+					color: cell.color(self.data, address),
+				});
+			}
+			let bounds = self.bounds.next()?;
+			self.current = Some(self.data.cells.range(bounds));
+		}
+	}
+}
+
+
+/// A cell of a `Data` and its evaluated color.
+#[derive(Debug, Clone)]
+pub struct CellEntry<'a> {
+	/// The address of the cell.
+	pub address: Address,
+	/// The cell.
+	pub cell: &'a Shared<Cell>,
+	/// The color of the cell, or `None` if it has no color.
+	pub color: Option<Color>,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// EmptySlots
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the addresses within the layout of a `Data` which contain 
+/// no cell.
+#[derive(Debug, Clone)]
+pub struct EmptySlots<'a> {
+	/// The `Data` being iterated over.
+	data: &'a Data,
+	/// The next candidate address.
+	next: Option<Address>,
+}
+
+
+impl<'a> Iterator for EmptySlots<'a> {
+	type Item = Address;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let address = self.next
+				.and_then(|next| self.data.next_valid_address(next));
+			self.next = address.and_then(|address| address.successor());
+
+			match address {
+				Some(address) if self.data.cells.contains_key(&address) => (),
+				_ => return address,
+			}
+		}
+	}
+}
@@ -0,0 +1,224 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides a graph for tracking the dependencies between `Cell`s.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Column, Line, Page};
+
+// Standard imports.
+use std::collections::{
+	BTreeMap,
+	BTreeSet,
+};
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DependencyGraph
+////////////////////////////////////////////////////////////////////////////////
+/// Records which `Cell`s are used as sources by the expressions of other 
+/// `Cell`s. Edges are stored in both directions so that both sources and 
+/// dependents can be queried cheaply.
+#[derive(Debug, Default, Clone)]
+pub struct DependencyGraph {
+	/// A map assigning addresses to the addresses they depend on.
+	sources: BTreeMap<Address, BTreeSet<Address>>,
+
+	/// A map assigning addresses to the addresses that depend on them.
+	dependents: BTreeMap<Address, BTreeSet<Address>>,
+}
+
+
+impl DependencyGraph {
+	/// Creates a new, empty `DependencyGraph`.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Replaces the sources of the given address.
+	pub fn link(&mut self, address: Address, sources: BTreeSet<Address>) {
+		self.unlink(address);
+		for &source in &sources {
+			self.dependents
+				.entry(source)
+				.or_insert_with(BTreeSet::new)
+				.insert(address);
+		}
+		if !sources.is_empty() {
+			self.sources.insert(address, sources);
+		}
+	}
+
+	/// Removes all of the sources of the given address.
+	pub fn unlink(&mut self, address: Address) {
+		if let Some(sources) = self.sources.remove(&address) {
+			for source in sources {
+				let now_empty = self.dependents
+					.get_mut(&source)
+					.map_or(false, |deps| {
+						deps.remove(&address);
+						deps.is_empty()
+					});
+				if now_empty {self.dependents.remove(&source);}
+			}
+		}
+	}
+
+	/// Returns the addresses the given address directly depends on.
+	pub fn sources(&self, address: Address) -> BTreeSet<Address> {
+		self.sources.get(&address).cloned().unwrap_or_default()
+	}
+
+	/// Returns the addresses which directly depend on the given address.
+	pub fn dependents(&self, address: Address) -> BTreeSet<Address> {
+		self.dependents.get(&address).cloned().unwrap_or_default()
+	}
+
+	/// Returns the addresses the given address depends on, either directly or
+	/// through other sources.
+	pub fn transitive_sources(&self, address: Address) -> BTreeSet<Address> {
+		closure(&self.sources, address)
+	}
+
+	/// Returns the addresses which depend on the given address, either 
+	/// directly or through other dependents.
+	pub fn transitive_dependents(&self, address: Address) -> BTreeSet<Address> {
+		closure(&self.dependents, address)
+	}
+
+	/// Returns whether giving the address the provided sources would 
+	/// introduce a cycle into the graph.
+	pub fn would_cycle(&self, address: Address, sources: &BTreeSet<Address>) 
+		-> bool 
+	{
+		sources.contains(&address) ||
+		!self.transitive_dependents(address).is_disjoint(sources)
+	}
+}
+
+
+/// Returns all addresses reachable from the given address along the given 
+/// edges, excluding the address itself unless it lies on a cycle.
+fn closure(
+	edges: &BTreeMap<Address, BTreeSet<Address>>, 
+	address: Address)
+	-> BTreeSet<Address>
+{
+	let mut visited = BTreeSet::new();
+	let mut pending = vec![address];
+
+	while let Some(next) = pending.pop() {
+		if let Some(targets) = edges.get(&next) {
+			for &target in targets {
+				if visited.insert(target) {
+					pending.push(target);
+				}
+			}
+		}
+	}
+	visited
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// WildcardIndex
+////////////////////////////////////////////////////////////////////////////////
+/// The page, line, and column selected by a resolved reference. A component is
+/// `None` if it selects every page, line, or column.
+pub(crate) type Coverage = (Option<Page>, Option<Line>, Option<Column>);
+
+
+/// Records the `Cell`s whose expressions reference other cells indirectly, 
+/// indexed by the pages, lines, and columns their references cover. This 
+/// allows the cells which may reference a new cell to be found without 
+/// resolving every indirect reference in the palette.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct WildcardIndex {
+	/// A map assigning addresses to the coverage of their references.
+	coverage: BTreeMap<Address, BTreeSet<Coverage>>,
+
+	/// A map assigning coverage to the addresses whose references have it.
+	dependents: BTreeMap<Coverage, BTreeSet<Address>>,
+}
+
+
+impl WildcardIndex {
+	/// Replaces the coverage of the given address. An address whose 
+	/// references could not be resolved is recorded with no coverage.
+	pub(crate) fn insert(
+		&mut self, 
+		address: Address, 
+		coverage: BTreeSet<Coverage>)
+	{
+		self.remove(address);
+		for &covered in &coverage {
+			self.dependents
+				.entry(covered)
+				.or_insert_with(BTreeSet::new)
+				.insert(address);
+		}
+		self.coverage.insert(address, coverage);
+	}
+
+	/// Removes the given address from the index.
+	pub(crate) fn remove(&mut self, address: Address) {
+		if let Some(coverage) = self.coverage.remove(&address) {
+			for covered in coverage {
+				let now_empty = self.dependents
+					.get_mut(&covered)
+					.map_or(false, |deps| {
+						deps.remove(&address);
+						deps.is_empty()
+					});
+				if now_empty {self.dependents.remove(&covered);}
+			}
+		}
+	}
+
+	/// Returns every address in the index.
+	pub(crate) fn addresses(&self) -> Vec<Address> {
+		self.coverage.keys().cloned().collect()
+	}
+
+	/// Returns the addresses whose references may select the given address.
+	pub(crate) fn covering(&self, address: Address) -> BTreeSet<Address> {
+		let mut addresses = BTreeSet::new();
+		for &page in &[Some(address.page), None] {
+			for &line in &[Some(address.line), None] {
+				for &column in &[Some(address.column), None] {
+					if let Some(deps) = self.dependents
+						.get(&(page, line, column)) 
+					{
+						addresses.extend(deps);
+					}
+				}
+			}
+		}
+		addresses
+	}
+}
@@ -0,0 +1,506 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides structural comparisons between palettes.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference, Page, Line, Column};
+use data::{Data, MetaData};
+use expression::Expression;
+use operation::{
+	DeleteCell,
+	DeleteName,
+	DefineName,
+	InsertCell,
+	PaletteOperation,
+	Sequence,
+	SetMetaData,
+};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::collections::BTreeSet;
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Diff
+////////////////////////////////////////////////////////////////////////////////
+/// The differences between two versions of a palette's `Data`.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::diff::Diff;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(0x10, 0x20, 0x30)))
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression("0:0:0".parse().unwrap())
+/// )).unwrap();
+/// let before = pal.snapshot();
+///
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(Address::new(0, 0, 0))
+/// 	.overwrite(true)
+/// 	.with_expression(Expression::Color(Color::new(0x40, 0x50, 0x60)))
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(0xff, 0, 0)))
+/// )).unwrap();
+/// pal.apply(Box::new(
+/// 	DefineName::new("base", Address::new(0, 0, 0).into())
+/// )).unwrap();
+/// let after = pal.snapshot();
+///
+/// let diff = Diff::new(&before, &after);
+/// assert_eq!(diff.to_string(), "\
+/// 	~ cell 0:0:0: #102030 -> #405060\n\
+/// 	~ color 0:0:1: #102030 -> #405060\n\
+/// 	+ cell 0:0:2: #ff0000\n\
+/// 	+ name base: 0:0:0\n");
+///
+/// // Turn the old palette into the new one.
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.restore(&before);
+/// pal.apply(Box::new(diff.to_operations())).unwrap();
+/// assert!(Diff::new(&pal.snapshot(), &after).is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+	/// The changes to the palette's cells, in address order.
+	pub cells: Vec<CellDiff>,
+
+	/// The changes to the palette's names, in name order.
+	pub names: Vec<NameDiff>,
+
+	/// The changes to the palette's metadata, in group order.
+	pub metadata: Vec<MetaDataDiff>,
+
+	/// The changes to the palette's layout. Line and column counts are only
+	/// compared for the pages and lines containing cells in either palette.
+	pub layout: Vec<LayoutDiff>,
+}
+
+
+impl Diff {
+	/// Returns the differences between the `old` and `new` `Data`.
+	pub fn new(old: &Data, new: &Data) -> Diff {
+		Diff {
+			cells: cell_diffs(old, new),
+			names: name_diffs(old, new),
+			metadata: metadata_diffs(old, new),
+			layout: layout_diffs(old, new),
+		}
+	}
+
+	/// Returns whether there are no differences.
+	pub fn is_empty(&self) -> bool {
+		self.cells.is_empty() && 
+		self.names.is_empty() &&
+		self.metadata.is_empty() &&
+		self.layout.is_empty()
+	}
+
+	/// Returns a `Sequence` of operations which will turn the old `Data` into 
+	/// the new `Data`. Changes to the layout itself cannot be made by 
+	/// operations, so only the differences in cells, names, and metadata are
+	/// applied.
+	pub fn to_operations(&self) -> Sequence {
+		let mut operations: Vec<Box<PaletteOperation>> = Vec::new();
+
+		// Remove cells and clear changed expressions first, so that no 
+		// intermediate state can contain a cyclic reference.
+		for cell in &self.cells {
+			match *cell {
+				CellDiff::Removed(address, _) => operations.push(
+					Box::new(DeleteCell::new(address))),
+				CellDiff::Changed(address, _, _) => operations.push(
+					Box::new(set_expression(address, Expression::Empty))),
+				_ => (),
+			}
+		}
+
+		for meta in &self.metadata {
+			operations.push(Box::new(
+				SetMetaData::new(meta.group.clone(), meta.new.clone())));
+		}
+
+		for name in &self.names {
+			match *name {
+				NameDiff::Defined(ref name, ref reference) |
+				NameDiff::Changed(ref name, _, ref reference) 
+					=> operations.push(Box::new(
+						DefineName::new(name.clone(), reference.clone()))),
+				NameDiff::Removed(ref name, _) 
+					=> operations.push(Box::new(DeleteName::new(name.clone()))),
+			}
+		}
+
+		for cell in &self.cells {
+			match *cell {
+				CellDiff::Added(address, ref expression) |
+				CellDiff::Changed(address, _, ref expression) 
+					=> operations.push(Box::new(
+						set_expression(address, expression.clone()))),
+				_ => (),
+			}
+		}
+
+		Sequence::new(operations)
+	}
+}
+
+
+/// Returns an operation storing the given expression at the given address.
+fn set_expression(address: Address, expression: Expression) -> InsertCell {
+	InsertCell::new()
+		.located_at(address)
+		.overwrite(true)
+		.with_expression(expression)
+}
+
+
+/// Returns the differences between the cells of the given `Data`.
+fn cell_diffs(old: &Data, new: &Data) -> Vec<CellDiff> {
+	let mut addresses: BTreeSet<Address> = BTreeSet::new();
+	addresses.extend(old.cells.keys());
+	addresses.extend(new.cells.keys());
+
+	let mut diffs = Vec::new();
+	for address in addresses {
+		match (old.cells.get(&address), new.cells.get(&address)) {
+			(Some(old_cell), Some(new_cell)) => {
+				let old_expr = old_cell.borrow();
+				let new_expr = new_cell.borrow();
+				if *old_expr != *new_expr {
+					diffs.push(CellDiff::Changed(
+						address, 
+						old_expr.clone(), 
+						new_expr.clone()));
+				} else if old.color(address) != new.color(address) {
+					diffs.push(CellDiff::Recolored(
+						address, 
+						old.color(address), 
+						new.color(address)));
+				}
+			},
+			(None, Some(new_cell)) => diffs.push(
+				CellDiff::Added(address, new_cell.borrow().clone())),
+			(Some(old_cell), None) => diffs.push(
+				CellDiff::Removed(address, old_cell.borrow().clone())),
+			(None, None) => unreachable!(),
+		}
+	}
+	diffs
+}
+
+
+/// Returns the differences between the names of the given `Data`.
+fn name_diffs(old: &Data, new: &Data) -> Vec<NameDiff> {
+	let names: BTreeSet<_> = old.names.keys()
+		.chain(new.names.keys())
+		.collect();
+
+	names.into_iter()
+		.filter_map(|name| match (old.names.get(name), new.names.get(name)) {
+			(Some(a), Some(b)) if a == b => None,
+			(Some(a), Some(b))
+				=> Some(NameDiff::Changed(name.clone(), a.clone(), b.clone())),
+			(None, Some(b)) => Some(NameDiff::Defined(name.clone(), b.clone())),
+			(Some(a), None) => Some(NameDiff::Removed(name.clone(), a.clone())),
+			(None, None) => None,
+		})
+		.collect()
+}
+
+
+/// Returns the differences between the metadata of the given `Data`.
+fn metadata_diffs(old: &Data, new: &Data) -> Vec<MetaDataDiff> {
+	let mut groups: Vec<_> = old.metadata.keys()
+		.chain(new.metadata.keys())
+		.collect();
+	groups.sort_by_key(|group| group.to_string());
+	groups.dedup();
+
+	groups.into_iter()
+		.map(|group| MetaDataDiff {
+			group: group.clone(),
+			old: old.metadata.get(group).cloned(),
+			new: new.metadata.get(group).cloned(),
+		})
+		.filter(|diff| diff.old != diff.new)
+		.collect()
+}
+
+
+/// Returns the differences between the layouts of the given `Data`.
+fn layout_diffs(old: &Data, new: &Data) -> Vec<LayoutDiff> {
+	let mut diffs = Vec::new();
+	let page_counts = (old.layout.page_count(), new.layout.page_count());
+	if page_counts.0 != page_counts.1 {
+		diffs.push(LayoutDiff::PageCount(page_counts.0, page_counts.1));
+	}
+
+	let lines: BTreeSet<_> = old.cells.keys()
+		.chain(new.cells.keys())
+		.map(|address| (address.page, address.line))
+		.collect();
+	let pages: BTreeSet<_> = lines.iter().map(|&(page, _)| page).collect();
+
+	for page in pages {
+		let group = Reference::page_of(&Address::new(page, 0, 0));
+		let old_lines = old.line_count(&group);
+		let new_lines = new.line_count(&group);
+		if old_lines != new_lines {
+			diffs.push(LayoutDiff::LineCount(page, old_lines, new_lines));
+		}
+	}
+	for (page, line) in lines {
+		let group = Reference::line_of(&Address::new(page, line, 0));
+		let old_columns = old.column_count(&group);
+		let new_columns = new.column_count(&group);
+		if old_columns != new_columns {
+			diffs.push(LayoutDiff::ColumnCount(
+				page, 
+				line, 
+				old_columns, 
+				new_columns));
+		}
+	}
+	diffs
+}
+
+
+impl fmt::Display for Diff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for diff in &self.layout {
+			writeln!(f, "{}", diff)?;
+		}
+		for diff in &self.metadata {
+			writeln!(f, "{}", diff)?;
+		}
+		for diff in &self.cells {
+			writeln!(f, "{}", diff)?;
+		}
+		for diff in &self.names {
+			writeln!(f, "{}", diff)?;
+		}
+		Ok(())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CellDiff
+////////////////////////////////////////////////////////////////////////////////
+/// A change to a single cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellDiff {
+	/// A cell was added with the given expression.
+	Added(Address, Expression),
+
+	/// A cell with the given expression was removed.
+	Removed(Address, Expression),
+
+	/// The expression of a cell was changed from the first expression to the
+	/// second.
+	Changed(Address, Expression, Expression),
+
+	/// The expression of a cell is unchanged, but its color was changed from 
+	/// the first color to the second by a change to its sources.
+	Recolored(Address, Option<Color>, Option<Color>),
+}
+
+
+impl fmt::Display for CellDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			CellDiff::Added(address, ref expression)
+				=> write!(f, "+ cell {}: {}", address, expression),
+
+			CellDiff::Removed(address, ref expression)
+				=> write!(f, "- cell {}: {}", address, expression),
+
+			CellDiff::Changed(address, ref old, ref new)
+				=> write!(f, "~ cell {}: {} -> {}", address, old, new),
+
+			CellDiff::Recolored(address, old, new)
+				=> write!(f, "~ color {}: {} -> {}", 
+					address, 
+					ColorText(old), 
+					ColorText(new)),
+		}
+	}
+}
+
+
+/// Displays an optional `Color`, or `-` if there is no color.
+struct ColorText(Option<Color>);
+
+
+impl fmt::Display for ColorText {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.0 {
+			Some(color) => write!(f, "#{:02x}{:02x}{:02x}",
+				color.red(),
+				color.green(),
+				color.blue()),
+			None => write!(f, "-"),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// NameDiff
+////////////////////////////////////////////////////////////////////////////////
+/// A change to a named reference.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameDiff {
+	/// A name was defined with the given reference.
+	Defined(String, Reference),
+
+	/// The definition of a name was removed.
+	Removed(String, Reference),
+
+	/// The definition of a name was changed from the first reference to the 
+	/// second.
+	Changed(String, Reference, Reference),
+}
+
+
+impl fmt::Display for NameDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			NameDiff::Defined(ref name, ref reference)
+				=> write!(f, "+ name {}: {}", name, Source(reference)),
+
+			NameDiff::Removed(ref name, ref reference)
+				=> write!(f, "- name {}: {}", name, Source(reference)),
+
+			NameDiff::Changed(ref name, ref old, ref new)
+				=> write!(f, "~ name {}: {} -> {}", 
+					name, 
+					Source(old), 
+					Source(new)),
+		}
+	}
+}
+
+
+/// Displays a `Reference` as an address if it refers to a single address.
+struct Source<'a>(&'a Reference);
+
+
+impl<'a> fmt::Display for Source<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.0.address() {
+			Ok(address) => write!(f, "{}", address),
+			Err(_) => write!(f, "{}", self.0),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MetaDataDiff
+////////////////////////////////////////////////////////////////////////////////
+/// A change to the metadata of a group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaDataDiff {
+	/// The group whose metadata changed.
+	pub group: Reference,
+
+	/// The old metadata of the group, if it had any.
+	pub old: Option<MetaData>,
+
+	/// The new metadata of the group, if it has any.
+	pub new: Option<MetaData>,
+}
+
+
+impl fmt::Display for MetaDataDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match (self.old.as_ref(), self.new.as_ref()) {
+			(Some(old), Some(new)) 
+				=> write!(f, "~ meta {}: {} -> {}", self.group, old, new),
+			(None, Some(new)) => write!(f, "+ meta {}: {}", self.group, new),
+			(Some(old), None) => write!(f, "- meta {}: {}", self.group, old),
+			(None, None) => write!(f, "~ meta {}", self.group),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LayoutDiff
+////////////////////////////////////////////////////////////////////////////////
+/// A change to the layout of the palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutDiff {
+	/// The number of pages was changed.
+	PageCount(Page, Page),
+
+	/// The number of lines in the given page was changed.
+	LineCount(Page, Line, Line),
+
+	/// The number of columns in the given line was changed.
+	ColumnCount(Page, Line, Column, Column),
+}
+
+
+impl fmt::Display for LayoutDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			LayoutDiff::PageCount(old, new)
+				=> write!(f, "~ layout: {} pages -> {} pages", old, new),
+
+			LayoutDiff::LineCount(page, old, new)
+				=> write!(f, "~ layout {}: {} lines -> {} lines", 
+					Reference::page_of(&Address::new(page, 0, 0)), 
+					old, 
+					new),
+
+			LayoutDiff::ColumnCount(page, line, old, new)
+				=> write!(f, "~ layout {}: {} columns -> {} columns", 
+					Reference::line_of(&Address::new(page, line, 0)), 
+					old, 
+					new),
+		}
+	}
+}
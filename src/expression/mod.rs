@@ -0,0 +1,553 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! The `expression` module provides functionality for implementing 
+//! color-expressions, which describe relationships and operations on colors in
+//! terms of an expression grammar and abstract syntax tree.
+//!
+//! Expressions are written using the following syntax:
+//!
+//! ```text
+//! #ff8800                     A pure color, given in hexadecimal.
+//! rgb(255, 136, 0)            A pure color, given by its channels.
+//! 0:1:2                       A copy of the color at an address.
+//! @base                       A copy of the color at a named reference.
+//! blend(0:1:2, 0:1:3, 0.25)   A blend of two colors.
+//! blend(@a, @b, 50%, oklch)   A blend of two colors in the given color space.
+//! mean(0:3:*)                 The average of the colors in a set of cells.
+//! mix(0:3:*, 1, 2, 1)         A weighted mix of the colors in a set of cells.
+//! lighten(@base, 10%)         A color lightened by the given amount.
+//! darken(@base, 0.1)          A color darkened by the given amount.
+//! saturate(@base, 10%)        A color saturated by the given amount.
+//! desaturate(@base, 10%)      A color desaturated by the given amount.
+//! rotate_hue(@base, 30)       A color with its hue rotated by some degrees.
+//! set_hue(@base, 120)         A color with the given hue, in degrees.
+//! set_saturation(@base, 50%)  A color with the given saturation.
+//! set_lightness(@base, 50%)   A color with the given lightness.
+//! ```
+//!
+//! Adjustments are made in the HSL color model unless an `hsv` argument is 
+//! given after the amount, as in `lighten(@base, 10%, hsv)`. In the HSV model, 
+//! the lightness of a color refers to its value.
+//!
+//! References may be given in any form accepted by `Reference`'s `FromStr` 
+//! implementation. A `*` in a reference selects every cell in that page or 
+//! line, as in `0:3:*` or `0:*:*`. The weights of a mix are given to the 
+//! referenced cells in address order, and any cells beyond the last weight are
+//! given a weight of 1. Empty cells are skipped.
+//!
+//! A `_` in a reference refers to the page, line, or column of the cell 
+//! holding the expression, and may be followed by an offset. For example, 
+//! `_:_:_-1` refers to the cell one column to the left, and `_:_-1:_` refers to
+//! the same column on the previous line. Expressions using these relative 
+//! references may be copied to other cells without being rewritten.
+//!
+//! Blends and mixes are made on the RGB channels unless a color space is given
+//! after the amount or weights. The supported color spaces are `rgb`, 
+//! `linear`, `lab`, `lch`, `oklab` and `oklch`, where `linear` blends the RGB
+//! channels in linear light rather than on their gamma-encoded values. The 
+//! polar color spaces `lch` and `oklch` may be followed by a hue interpolation
+//! mode: `shorter` (the default), `longer`, `increasing` or `decreasing`, as 
+//! in `blend(@a, @b, 0.5, lch, longer)`. Mixes average the hues of their 
+//! colors on the hue circle, and so ignore the hue interpolation mode.
+//!
+//! An empty string denotes an empty expression.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Submodules.
+#[warn(missing_docs)]
+mod parse;
+
+// Submodule re-exports.
+pub use self::parse::{
+	ParseError,
+	ParseErrorKind,
+	Span,
+};
+
+// Local imports.
+use address::{Address, Reference};
+use colorspace::{ColorSpace, HueModel, normalize_hue};
+use data::Data;
+use utilities::clamped;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::fmt;
+use std::str::FromStr;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Expression
+////////////////////////////////////////////////////////////////////////////////
+/// An AST in the color-expression grammar.
+#[derive(Clone, PartialEq)]
+pub enum Expression {
+	/// An empty expression.
+	Empty,
+	/// A pure color. (Zeroth order.)
+	Color(Color),
+	/// A copy of the color in the referenced cell. (First order.)
+	Reference(Reference),
+	/// A blend of the colors in the referenced cells, interpolated in the 
+	/// given color space. The amount gives the ratio of the second color in 
+	/// the blend. (Second order.)
+	Blend(Reference, Reference, f32, ColorSpace),
+	/// An adjustment of the color in the referenced cell, made in the given
+	/// color model. (First order.)
+	Adjust(Reference, Adjustment, HueModel),
+	/// A weighted mix of the colors in all of the referenced cells, made in 
+	/// the given color space. The weights are given to the cells in address
+	/// order, and any cells without a weight are given a weight of 1.
+	Mix(Reference, Vec<f32>, ColorSpace),
+}
+
+
+impl Expression {
+	/// Returns the `Color` generated by the expression, resolving any
+	/// referenced cells through the given `Data` relative to the `base` 
+	/// address, which is normally the address of the cell holding the 
+	/// expression. Returns `None` if the expression is empty or any of its 
+	/// sources are empty or invalid.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color, Expression};
+	/// use palette::colorspace::ColorSpace;
+	///
+	/// let mut dat: Data = Default::default();
+	/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+	/// *dat.create_cell(a).unwrap().borrow_mut()
+	/// 	= Expression::Color(Color::new(0, 0, 0));
+	/// *dat.create_cell(b).unwrap().borrow_mut()
+	/// 	= Expression::Color(Color::new(100, 200, 50));
+	///
+	/// let blend = Expression::Blend(a.into(), b.into(), 0.5, ColorSpace::Rgb);
+	/// assert_eq!(blend.color(&dat, a), Some(Color::new(50, 100, 25)));
+	/// ```
+	///
+	/// A mix follows the set of cells it references as cells are added:
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color, Expression};
+	///
+	/// let mut dat: Data = Default::default();
+	/// let neutral = Address::new(0, 0, 0);
+	/// dat.create_cell(neutral).unwrap();
+	/// dat.set_expression(neutral, "mean(0:3:*)".parse().unwrap()).unwrap();
+	/// assert_eq!(dat.color(neutral), None);
+	///
+	/// for (column, &value) in [0, 60, 120].iter().enumerate() {
+	/// 	let address = Address::new(0, 3, column as u8);
+	/// 	dat.create_cell(address).unwrap();
+	/// 	dat.set_expression(
+	/// 		address, 
+	/// 		Expression::Color(Color::new(value, value, value))
+	/// 	).unwrap();
+	/// }
+	/// assert_eq!(dat.color(neutral), Some(Color::new(60, 60, 60)));
+	///
+	/// // Cells in the line may not reference the mix.
+	/// let copy = Address::new(0, 3, 3);
+	/// dat.create_cell(copy).unwrap();
+	/// assert!(dat.set_expression(copy, Expression::Reference(neutral.into()))
+	/// 	.is_err());
+	/// ```
+	pub fn color(&self, data: &Data, base: Address) -> Option<Color> {
+		match *self {
+			Expression::Empty => None,
+
+			Expression::Color(color) => Some(color),
+
+			Expression::Reference(ref reference)
+				=> source_color(data, reference, base),
+
+			Expression::Blend(ref a, ref b, amount, space) => {
+				let a = source_color(data, a, base)?;
+				let b = source_color(data, b, base)?;
+				Some(space.interpolate(a, b, amount))
+			},
+
+			Expression::Adjust(ref reference, adjustment, model)
+				=> source_color(data, reference, base)
+					.map(|color| adjustment.apply(color, model)),
+
+			Expression::Mix(ref reference, ref weights, space) => {
+				let addresses = data.matching_addresses(reference, base);
+				space.mix(addresses
+					.into_iter()
+					.enumerate()
+					.filter_map(|(i, address)| data.color(address)
+						.map(|color| (
+							color, 
+							weights.get(i).cloned().unwrap_or(1.0)
+						))))
+			},
+		}
+	}
+
+	/// Returns the `Reference`s to the source cells of the expression.
+	pub fn references(&self) -> Vec<&Reference> {
+		match *self {
+			Expression::Empty |
+			Expression::Color(..) => Vec::new(),
+
+			Expression::Reference(ref reference) |
+			Expression::Adjust(ref reference, _, _) |
+			Expression::Mix(ref reference, _, _) => vec![reference],
+
+			Expression::Blend(ref a, ref b, _, _) => vec![a, b],
+		}
+	}
+
+	/// Returns mutable `Reference`s to the source cells of the expression.
+	pub fn references_mut(&mut self) -> Vec<&mut Reference> {
+		match *self {
+			Expression::Empty |
+			Expression::Color(..) => Vec::new(),
+
+			Expression::Reference(ref mut reference) |
+			Expression::Adjust(ref mut reference, _, _) |
+			Expression::Mix(ref mut reference, _, _) => vec![reference],
+
+			Expression::Blend(ref mut a, ref mut b, _, _) => vec![a, b],
+		}
+	}
+}
+
+
+/// Returns the color of the cell referenced by the given `Reference`, or `None`
+/// if the reference does not resolve to a single non-empty cell.
+fn source_color(data: &Data, reference: &Reference, base: Address) 
+	-> Option<Color> 
+{
+	if let Ok(address) = reference.address() {
+		return data.color(address);
+	}
+
+	let addresses = data.matching_addresses(reference, base);
+	if addresses.len() == 1 {
+		data.color(addresses[0])
+	} else {
+		None
+	}
+}
+
+
+impl fmt::Debug for Expression {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		match *self {
+			Expression::Empty
+				=> write!(f, "Expression::Empty"),
+
+			Expression::Color(ref color)
+				=> write!(f, "Expression::Color({:?})", color),
+
+			Expression::Reference(ref reference)
+				=> write!(f, "Expression::Reference({:?})", reference),
+
+			Expression::Blend(ref a, ref b, amount, ref space)
+				=> write!(f, "Expression::Blend({:?}, {:?}, {:?}, {:?})", 
+					a, b, amount, space),
+
+			Expression::Adjust(ref reference, ref adjustment, ref model)
+				=> write!(f, "Expression::Adjust({:?}, {:?}, {:?})", 
+					reference, adjustment, model),
+
+			Expression::Mix(ref reference, ref weights, ref space)
+				=> write!(f, "Expression::Mix({:?}, {:?}, {:?})", 
+					reference, weights, space),
+		}
+	}
+}
+
+
+// Display `Expression` using the expression grammar.
+impl fmt::Display for Expression {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Expression::Empty => Ok(()),
+
+			Expression::Color(ref color) => write!(f, "#{:02x}{:02x}{:02x}",
+				color.red(),
+				color.green(),
+				color.blue()),
+
+			Expression::Reference(ref reference)
+				=> write!(f, "{}", Source(reference)),
+
+			Expression::Blend(ref a, ref b, amount, space) => {
+				write!(f, "blend({}, {}, {}", Source(a), Source(b), amount)?;
+				if space != ColorSpace::default() {
+					write!(f, ", {}", space)?;
+				}
+				write!(f, ")")
+			},
+
+			Expression::Adjust(ref reference, ref adjustment, model) => {
+				write!(f, "{}({}, {}", 
+					adjustment.function_name(),
+					Source(reference), 
+					adjustment.amount())?;
+				if model != HueModel::default() {
+					write!(f, ", {}", model)?;
+				}
+				write!(f, ")")
+			},
+
+			Expression::Mix(ref reference, ref weights, space) => {
+				if weights.is_empty() {
+					write!(f, "mean({}", Source(reference))?;
+				} else {
+					write!(f, "mix({}", Source(reference))?;
+				}
+				for weight in weights {
+					write!(f, ", {}", weight)?;
+				}
+				if space != ColorSpace::default() {
+					write!(f, ", {}", space)?;
+				}
+				write!(f, ")")
+			},
+		}
+	}
+}
+
+
+/// Parses an `Expression` from the expression grammar.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Expression;
+/// use palette::expression::ParseErrorKind;
+///
+/// let text = "blend(0:1:2, 0:1:3, 0.25)";
+/// let expr: Expression = text.parse().unwrap();
+///
+/// assert_eq!(expr.to_string(), text);
+/// assert_eq!(expr.to_string().parse::<Expression>().unwrap(), expr);
+///
+/// let expr: Expression = "blend(@base, 0:1:3, 10%)".parse().unwrap();
+/// assert_eq!(expr.to_string().parse::<Expression>().unwrap(), expr);
+///
+/// let expr: Expression = "lighten(@base, 10%)".parse().unwrap();
+/// assert_eq!(expr.to_string().parse::<Expression>().unwrap(), expr);
+///
+/// let expr: Expression = "blend(@a, @b, 0.5, oklch, longer)".parse().unwrap();
+/// assert_eq!(expr.to_string(), "blend(@a, @b, 0.5, oklch, longer)");
+///
+/// let expr: Expression = "blend(@a, @b, 0.5, linear)".parse().unwrap();
+/// assert_eq!(expr.to_string(), "blend(@a, @b, 0.5, linear)");
+///
+/// let expr: Expression = "mix(0:3:*, 1, 0.5, oklab)".parse().unwrap();
+/// assert_eq!(expr.to_string(), "mix(0:3:*, 1, 0.5, oklab)");
+///
+/// let expr: Expression = "lighten(_:_:_-1, 5%)".parse().unwrap();
+/// assert_eq!(expr.to_string(), "lighten(_:_:_-1, 0.05)");
+/// assert_eq!(expr.to_string().parse::<Expression>().unwrap(), expr);
+///
+/// let err = "blend(0:1:2, @base)".parse::<Expression>().unwrap_err();
+/// assert_eq!((err.span.start, err.span.end), (0, 19));
+///
+/// let err = "mix(0:3:*, 1, 0.x)".parse::<Expression>().unwrap_err();
+/// assert_eq!(err.kind, ParseErrorKind::InvalidNumber);
+/// assert_eq!((err.span.start, err.span.end), (14, 17));
+///
+/// let err = "lighten(@base, inf)".parse::<Expression>().unwrap_err();
+/// assert_eq!(err.kind, ParseErrorKind::InvalidNumber);
+/// ```
+impl FromStr for Expression {
+	type Err = ParseError;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		parse::parse_expression(text)
+	}
+}
+
+
+impl Default for Expression {
+	fn default() -> Self {
+		Expression::Empty
+	}
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Adjustment
+////////////////////////////////////////////////////////////////////////////////
+/// An adjustment applied to a single source color. Saturation and lightness
+/// amounts are given in the range [0, 1], while hue amounts are given in 
+/// degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Adjustment {
+	/// Increases the lightness of the color by the given amount.
+	Lighten(f32),
+	/// Decreases the lightness of the color by the given amount.
+	Darken(f32),
+	/// Increases the saturation of the color by the given amount.
+	Saturate(f32),
+	/// Decreases the saturation of the color by the given amount.
+	Desaturate(f32),
+	/// Rotates the hue of the color by the given number of degrees.
+	RotateHue(f32),
+	/// Sets the hue of the color.
+	SetHue(f32),
+	/// Sets the saturation of the color.
+	SetSaturation(f32),
+	/// Sets the lightness of the color.
+	SetLightness(f32),
+}
+
+
+impl Adjustment {
+	/// Returns the `Adjustment` for the given expression function name, or 
+	/// `None` if the name is not an adjustment.
+	pub fn from_function_name(name: &str, amount: f32) -> Option<Adjustment> {
+		use self::Adjustment::*;
+		match name {
+			"lighten"			=> Some(Lighten(amount)),
+			"darken"			=> Some(Darken(amount)),
+			"saturate"			=> Some(Saturate(amount)),
+			"desaturate"		=> Some(Desaturate(amount)),
+			"rotate_hue"		=> Some(RotateHue(amount)),
+			"set_hue"			=> Some(SetHue(amount)),
+			"set_saturation"	=> Some(SetSaturation(amount)),
+			"set_lightness"		=> Some(SetLightness(amount)),
+			_					=> None,
+		}
+	}
+
+	/// Returns the name of the adjustment's expression function.
+	pub fn function_name(&self) -> &'static str {
+		use self::Adjustment::*;
+		match *self {
+			Lighten(..)			=> "lighten",
+			Darken(..)			=> "darken",
+			Saturate(..)		=> "saturate",
+			Desaturate(..)		=> "desaturate",
+			RotateHue(..)		=> "rotate_hue",
+			SetHue(..)			=> "set_hue",
+			SetSaturation(..)	=> "set_saturation",
+			SetLightness(..)	=> "set_lightness",
+		}
+	}
+
+	/// Returns the amount of the adjustment.
+	pub fn amount(&self) -> f32 {
+		use self::Adjustment::*;
+		match *self {
+			Lighten(amount)			|
+			Darken(amount)			|
+			Saturate(amount)		|
+			Desaturate(amount)		|
+			RotateHue(amount)		|
+			SetHue(amount)			|
+			SetSaturation(amount)	|
+			SetLightness(amount)	=> amount,
+		}
+	}
+
+	/// Returns the given color with the adjustment applied in the given color
+	/// model.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::colorspace::HueModel;
+	/// use palette::expression::Adjustment;
+	///
+	/// let red = Color::new(255, 0, 0);
+	/// 
+	/// assert_eq!(Adjustment::Lighten(0.25).apply(red, HueModel::Hsl),
+	/// 	Color::new(255, 128, 128));
+	/// assert_eq!(Adjustment::Darken(0.5).apply(red, HueModel::Hsv),
+	/// 	Color::new(128, 0, 0));
+	/// assert_eq!(Adjustment::RotateHue(120.0).apply(red, HueModel::Hsl),
+	/// 	Color::new(0, 255, 0));
+	/// assert_eq!(Adjustment::SetSaturation(0.0).apply(red, HueModel::Hsv),
+	/// 	Color::new(255, 255, 255));
+	/// ```
+	pub fn apply(&self, color: Color, model: HueModel) -> Color {
+		use self::Adjustment::*;
+		let (mut h, mut s, mut l) = model.decompose(color);
+		match *self {
+			Lighten(amount)			=> l = clamped(l + amount, 0.0, 1.0),
+			Darken(amount)			=> l = clamped(l - amount, 0.0, 1.0),
+			Saturate(amount)		=> s = clamped(s + amount, 0.0, 1.0),
+			Desaturate(amount)		=> s = clamped(s - amount, 0.0, 1.0),
+			RotateHue(amount)		=> h = normalize_hue(h + amount),
+			SetHue(amount)			=> h = normalize_hue(amount),
+			SetSaturation(amount)	=> s = clamped(amount, 0.0, 1.0),
+			SetLightness(amount)	=> l = clamped(amount, 0.0, 1.0),
+		}
+		model.compose(h, s, l)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Source
+////////////////////////////////////////////////////////////////////////////////
+/// Wraps a source `Reference` for display in the expression grammar.
+struct Source<'a>(&'a Reference);
+
+
+impl<'a> fmt::Display for Source<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let reference = self.0;
+		match (reference.page(), reference.line(), reference.column()) {
+			(Ok(page), Ok(line), Ok(column)) 
+				=> return write!(f, "{}:{}:{}", page, line, column),
+
+			(Ok(page), Ok(line), Err(_)) if *reference == 
+				Reference::line_of(&Address::new(page, line, 0))
+				=> return write!(f, "{}:{}:*", page, line),
+
+			(Ok(page), Err(_), Err(_)) if *reference == 
+				Reference::page_of(&Address::new(page, 0, 0))
+				=> return write!(f, "{}:*:*", page),
+
+			_ if *reference == Reference::all() 
+				=> return write!(f, "*:*:*"),
+
+			_ => (),
+		}
+
+		if let Some(name) = self.0.as_name() {
+			write!(f, "@{}", name)
+		} else {
+			self.0.write_separated(f, ':')
+		}
+	}
+}
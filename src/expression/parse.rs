@@ -0,0 +1,568 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides a parser for the color-expression grammar.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Reference;
+use colorspace::{ColorSpace, HueInterpolation, HueModel};
+use expression::{Adjustment, Expression};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::error;
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Span
+////////////////////////////////////////////////////////////////////////////////
+/// A range of byte offsets into the parsed text.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct Span {
+	/// The offset of the first byte in the span.
+	pub start: usize,
+	/// The offset one past the last byte in the span.
+	pub end: usize,
+}
+
+
+impl Span {
+	/// Creates a new `Span`.
+	pub fn new(start: usize, end: usize) -> Self {
+		Span {
+			start: start,
+			end: end,
+		}
+	}
+
+	/// Returns the smallest `Span` covering both spans.
+	pub fn to(self, other: Span) -> Span {
+		Span::new(self.start, other.end)
+	}
+}
+
+
+impl fmt::Display for Span {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}..{}", self.start, self.end)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ParseError
+////////////////////////////////////////////////////////////////////////////////
+/// An error produced when parsing a color-expression.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+	/// The kind of error.
+	pub kind: ParseErrorKind,
+	/// The location of the error in the parsed text.
+	pub span: Span,
+}
+
+
+impl ParseError {
+	/// Creates a new `ParseError`.
+	pub fn new(kind: ParseErrorKind, span: Span) -> Self {
+		ParseError {
+			kind: kind,
+			span: span,
+		}
+	}
+}
+
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.kind {
+			ParseErrorKind::UnknownFunction(ref name) => write!(f, 
+				"{} '{}' at {}", 
+				error::Error::description(self),
+				name,
+				self.span),
+
+			ParseErrorKind::ArgumentCount {expected, found} => write!(f, 
+				"{} (expected {}, found {}) at {}", 
+				error::Error::description(self), 
+				expected,
+				found,
+				self.span),
+
+			_ => write!(f, "{} at {}", 
+				error::Error::description(self), 
+				self.span),
+		}
+	}
+}
+
+
+impl error::Error for ParseError {
+	fn description(&self) -> &str {
+		match self.kind {
+			ParseErrorKind::UnexpectedEnd
+				=> "unexpected end of expression",
+
+			ParseErrorKind::UnexpectedToken
+				=> "unexpected token in expression",
+
+			ParseErrorKind::UnknownFunction(..)
+				=> "unknown expression function",
+
+			ParseErrorKind::ArgumentCount {..}
+				=> "wrong number of function arguments",
+
+			ParseErrorKind::InvalidNumber
+				=> "invalid number",
+
+			ParseErrorKind::InvalidColor
+				=> "invalid color",
+
+			ParseErrorKind::InvalidReference
+				=> "invalid cell reference",
+
+			ParseErrorKind::InvalidOption
+				=> "invalid function option",
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ParseErrorKind
+////////////////////////////////////////////////////////////////////////////////
+/// The kinds of errors produced when parsing a color-expression.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum ParseErrorKind {
+	/// The expression ended before it was complete.
+	UnexpectedEnd,
+	/// A token was found where it is not allowed.
+	UnexpectedToken,
+	/// A function name was not recognized.
+	UnknownFunction(String),
+	/// A function was called with the wrong number of arguments.
+	ArgumentCount {
+		/// The number of arguments the function accepts.
+		expected: usize,
+		/// The number of arguments provided.
+		found: usize,
+	},
+	/// A number was malformed or out of range.
+	InvalidNumber,
+	/// A color was malformed.
+	InvalidColor,
+	/// A cell reference was malformed.
+	InvalidReference,
+	/// A function option was not recognized.
+	InvalidOption,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Token
+////////////////////////////////////////////////////////////////////////////////
+/// A lexical token in the expression grammar.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Token<'t> {
+	/// An opening parenthesis.
+	Open,
+	/// A closing parenthesis.
+	Close,
+	/// An argument separator.
+	Comma,
+	/// Any other run of non-whitespace characters.
+	Atom(&'t str),
+}
+
+
+/// Splits the given text into tokens and their spans.
+fn tokenize(text: &str) -> Vec<(Token, Span)> {
+	let mut tokens = Vec::new();
+	let mut atom_start = None;
+
+	for (idx, c) in text.char_indices() {
+		let token = match c {
+			'(' => Some(Token::Open),
+			')' => Some(Token::Close),
+			',' => Some(Token::Comma),
+			_ if c.is_whitespace() => None,
+			_ => {
+				if atom_start.is_none() {atom_start = Some(idx);}
+				continue;
+			},
+		};
+
+		// Any non-atom character ends the current atom.
+		if let Some(start) = atom_start.take() {
+			tokens.push((Token::Atom(&text[start..idx]), Span::new(start, idx)));
+		}
+		if let Some(token) = token {
+			tokens.push((token, Span::new(idx, idx + c.len_utf8())));
+		}
+	}
+
+	if let Some(start) = atom_start {
+		tokens.push((
+			Token::Atom(&text[start..]), 
+			Span::new(start, text.len())
+		));
+	}
+	tokens
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Parser
+////////////////////////////////////////////////////////////////////////////////
+/// A recursive descent parser over the expression tokens.
+struct Parser<'t> {
+	/// The tokens being parsed.
+	tokens: Vec<(Token<'t>, Span)>,
+	/// The index of the next token.
+	position: usize,
+	/// The length of the parsed text.
+	end: usize,
+}
+
+
+/// Parses an `Expression` from the given text.
+pub fn parse_expression(text: &str) -> Result<Expression, ParseError> {
+	let mut parser = Parser {
+		tokens: tokenize(text),
+		position: 0,
+		end: text.len(),
+	};
+
+	let expr = parser.expression()?;
+	// Ensure all of the input was consumed.
+	match parser.next() {
+		Some((_, span)) => Err(ParseError::new(
+			ParseErrorKind::UnexpectedToken, 
+			span
+		)),
+		None => Ok(expr),
+	}
+}
+
+
+impl<'t> Parser<'t> {
+	/// Returns the next token without consuming it.
+	fn peek(&self) -> Option<Token<'t>> {
+		self.tokens.get(self.position).map(|&(token, _)| token)
+	}
+
+	/// Consumes and returns the next token.
+	fn next(&mut self) -> Option<(Token<'t>, Span)> {
+		let next = self.tokens.get(self.position).cloned();
+		if next.is_some() {self.position += 1;}
+		next
+	}
+
+	/// Returns an error for text ending unexpectedly.
+	fn unexpected_end(&self) -> ParseError {
+		ParseError::new(
+			ParseErrorKind::UnexpectedEnd, 
+			Span::new(self.end, self.end)
+		)
+	}
+
+	/// Parses a complete expression.
+	fn expression(&mut self) -> Result<Expression, ParseError> {
+		match self.next() {
+			None => Ok(Expression::Empty),
+
+			Some((Token::Atom(atom), span)) => {
+				if self.peek() == Some(Token::Open) {
+					self.call(atom, span)
+				} else if atom.starts_with('#') {
+					parse_color(atom, span).map(Expression::Color)
+				} else if is_reference(atom) {
+					parse_reference(atom, span).map(Expression::Reference)
+				} else {
+					Err(ParseError::new(ParseErrorKind::UnexpectedToken, span))
+				}
+			},
+
+			Some((_, span)) => Err(ParseError::new(
+				ParseErrorKind::UnexpectedToken, 
+				span
+			)),
+		}
+	}
+
+	/// Parses a function call with the given name.
+	fn call(&mut self, name: &str, name_span: Span) 
+		-> Result<Expression, ParseError> 
+	{
+		// Consume the opening parenthesis.
+		self.next();
+		let (args, close_span) = self.arguments()?;
+		let span = name_span.to(close_span);
+
+		match name {
+			"rgb" => {
+				check_arguments(&args, 3, span)?;
+				Ok(Expression::Color(Color::new(
+					parse_octet(args[0].0, args[0].1)?,
+					parse_octet(args[1].0, args[1].1)?,
+					parse_octet(args[2].0, args[2].1)?,
+				)))
+			},
+
+			"blend" => {
+				if args.len() < 3 {
+					check_arguments(&args, 3, span)?;
+				} else if args.len() > 5 {
+					check_arguments(&args, 5, span)?;
+				}
+				Ok(Expression::Blend(
+					parse_reference(args[0].0, args[0].1)?,
+					parse_reference(args[1].0, args[1].1)?,
+					parse_number(args[2].0, args[2].1)?,
+					parse_color_space(&args[3..])?,
+				))
+			},
+
+			"mean" => {
+				if args.is_empty() {check_arguments(&args, 1, span)?;}
+				if args.len() > 3 {check_arguments(&args, 3, span)?;}
+				Ok(Expression::Mix(
+					parse_reference(args[0].0, args[0].1)?,
+					Vec::new(),
+					parse_color_space(&args[1..])?,
+				))
+			},
+
+			"mix" => {
+				if args.is_empty() {check_arguments(&args, 1, span)?;}
+				let weights = args[1..]
+					.iter()
+					.take_while(|&&(atom, _)| !is_option(atom))
+					.map(|&(atom, span)| parse_number(atom, span))
+					.collect::<Result<Vec<_>, _>>()?;
+				let options = &args[1 + weights.len()..];
+				if options.len() > 2 {
+					check_arguments(&args, 3 + weights.len(), span)?;
+				}
+				Ok(Expression::Mix(
+					parse_reference(args[0].0, args[0].1)?,
+					weights,
+					parse_color_space(options)?,
+				))
+			},
+
+			_ if Adjustment::from_function_name(name, 0.0).is_some() => {
+				if args.len() < 2 {
+					check_arguments(&args, 2, span)?;
+				} else if args.len() > 3 {
+					check_arguments(&args, 3, span)?;
+				}
+				let amount = parse_number(args[1].0, args[1].1)?;
+				let model = match args.get(2) {
+					Some(&(atom, span)) => parse_hue_model(atom, span)?,
+					None => HueModel::default(),
+				};
+				Ok(Expression::Adjust(
+					parse_reference(args[0].0, args[0].1)?,
+					Adjustment::from_function_name(name, amount)
+						.expect("valid adjustment name"),
+					model
+				))
+			},
+
+			_ => Err(ParseError::new(
+				ParseErrorKind::UnknownFunction(name.to_owned()), 
+				name_span
+			)),
+		}
+	}
+
+	/// Parses a comma-separated argument list and the closing parenthesis.
+	/// Returns the arguments and the span of the closing parenthesis.
+	fn arguments(&mut self) -> Result<(Vec<(&'t str, Span)>, Span), ParseError> {
+		let mut args = Vec::new();
+		if self.peek() == Some(Token::Close) {
+			let (_, span) = self.next().expect("peeked token");
+			return Ok((args, span));
+		}
+
+		loop {
+			match self.next() {
+				Some((Token::Atom(atom), span)) => args.push((atom, span)),
+				Some((_, span)) => return Err(ParseError::new(
+					ParseErrorKind::UnexpectedToken, 
+					span
+				)),
+				None => return Err(self.unexpected_end()),
+			}
+
+			match self.next() {
+				Some((Token::Comma, _)) => continue,
+				Some((Token::Close, span)) => return Ok((args, span)),
+				Some((_, span)) => return Err(ParseError::new(
+					ParseErrorKind::UnexpectedToken, 
+					span
+				)),
+				None => return Err(self.unexpected_end()),
+			}
+		}
+	}
+}
+
+
+/// Returns an error if the wrong number of arguments were provided to a
+/// function call.
+fn check_arguments(args: &[(&str, Span)], expected: usize, span: Span) 
+	-> Result<(), ParseError>
+{
+	if args.len() == expected {
+		Ok(())
+	} else {
+		Err(ParseError::new(
+			ParseErrorKind::ArgumentCount {
+				expected: expected, 
+				found: args.len()
+			}, 
+			span
+		))
+	}
+}
+
+
+/// Returns whether the given atom should be parsed as a `Reference`.
+fn is_reference(atom: &str) -> bool {
+	atom.starts_with('@') || atom.contains(':') || atom.contains('/')
+}
+
+
+/// Returns whether the given atom should be parsed as a function option rather
+/// than a number.
+fn is_option(atom: &str) -> bool {
+	atom.parse::<f32>().is_err()
+		&& atom.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+
+/// Parses a `Reference` atom.
+fn parse_reference(atom: &str, span: Span) -> Result<Reference, ParseError> {
+	atom.parse()
+		.map_err(|_| ParseError::new(ParseErrorKind::InvalidReference, span))
+}
+
+
+/// Parses a number atom. A trailing '%' divides the number by 100. Numbers 
+/// which are not finite are rejected.
+fn parse_number(atom: &str, span: Span) -> Result<f32, ParseError> {
+	let invalid = || ParseError::new(ParseErrorKind::InvalidNumber, span);
+
+	let value = if atom.ends_with('%') {
+		atom[..atom.len() - 1]
+			.parse::<f32>()
+			.map(|value| value / 100.0)
+			.map_err(|_| invalid())?
+	} else {
+		atom.parse::<f32>().map_err(|_| invalid())?
+	};
+
+	if value.is_finite() {Ok(value)} else {Err(invalid())}
+}
+
+
+/// Parses a `HueModel` option atom.
+fn parse_hue_model(atom: &str, span: Span) -> Result<HueModel, ParseError> {
+	match atom {
+		"hsl" => Ok(HueModel::Hsl),
+		"hsv" => Ok(HueModel::Hsv),
+		_ => Err(ParseError::new(ParseErrorKind::InvalidOption, span)),
+	}
+}
+
+
+/// Parses the `ColorSpace` option atoms of a blend, which consist of a color 
+/// space name optionally followed by a hue interpolation mode.
+fn parse_color_space(args: &[(&str, Span)]) -> Result<ColorSpace, ParseError> {
+	let (atom, span) = match args.first() {
+		Some(&(atom, span)) => (atom, span),
+		None => return Ok(ColorSpace::default()),
+	};
+	let invalid = |span| ParseError::new(ParseErrorKind::InvalidOption, span);
+
+	let hue_interpolation = match args.get(1) {
+		Some(&("shorter", _))    => Some(HueInterpolation::Shorter),
+		Some(&("longer", _))     => Some(HueInterpolation::Longer),
+		Some(&("increasing", _)) => Some(HueInterpolation::Increasing),
+		Some(&("decreasing", _)) => Some(HueInterpolation::Decreasing),
+		Some(&(_, span))         => return Err(invalid(span)),
+		None                     => None,
+	};
+
+	match (atom, hue_interpolation) {
+		("rgb", None)    => Ok(ColorSpace::Rgb),
+		("linear", None) => Ok(ColorSpace::LinearRgb),
+		("lab", None)    => Ok(ColorSpace::Lab),
+		("oklab", None)  => Ok(ColorSpace::Oklab),
+		("lch", hue)     => Ok(ColorSpace::Lch(hue.unwrap_or_default())),
+		("oklch", hue)   => Ok(ColorSpace::Oklch(hue.unwrap_or_default())),
+		("rgb", _) | ("linear", _) | ("lab", _) | ("oklab", _) 
+			=> Err(invalid(args[1].1)),
+		_ => Err(invalid(span)),
+	}
+}
+
+
+/// Parses a color channel atom.
+fn parse_octet(atom: &str, span: Span) -> Result<u8, ParseError> {
+	atom.parse::<u8>()
+		.map_err(|_| ParseError::new(ParseErrorKind::InvalidNumber, span))
+}
+
+
+/// Parses a hexadecimal color atom of the form `#rgb` or `#rrggbb`.
+fn parse_color(atom: &str, span: Span) -> Result<Color, ParseError> {
+	let invalid = || ParseError::new(ParseErrorKind::InvalidColor, span);
+	let digits = &atom[1..];
+	if !digits.chars().all(|c| c.is_digit(16)) {return Err(invalid());}
+
+	let channel = |i: usize, width: usize| {
+		u8::from_str_radix(&digits[i * width..(i + 1) * width], 16)
+			.map(|c| if width == 1 {c * 17} else {c})
+			.map_err(|_| invalid())
+	};
+
+	match digits.len() {
+		3 => Ok(Color::new(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+		6 => Ok(Color::new(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+		_ => Err(invalid()),
+	}
+}
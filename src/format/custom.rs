@@ -0,0 +1,242 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides user-defined palette layouts, which are read from layout 
+//! description files.
+//!
+//! A layout description is a line-based text format. Each line consists of a 
+//! keyword followed by its arguments:
+//!
+//! ```text
+//! # NES palette layout.        A comment.
+//! pages 2                      The number of pages.
+//! lines 4                      The default number of lines in each page.
+//! columns 4                    The default number of columns in each line.
+//! meta 1/*/* lines 2           The line count of a page.
+//! meta 1/0/* columns 3         The column count of a line.
+//! meta 0/*/* label "BG"        The label of a page or line.
+//! meta 0/*/* name "Background" The initial name of a group.
+//! ```
+//!
+//! Any counts which are not given default to the largest counts allowed by an
+//! `Address`. Blank lines are ignored.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference, Page, Line, Column};
+use data::{Data, MetaData};
+use format::{
+	invalid_line,
+	metadata_lines,
+	parse_metadata_line,
+	split_word,
+};
+use layout::{GridLayout, Layout};
+use sync::Shared;
+
+// Standard imports.
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+use std::str::FromStr;
+
+
+
+/// Called when a new palette is created. Initializes the palette data with 
+/// the given layout and the names it provides.
+pub fn initialize(data: &mut Data, layout: &Shared<CustomLayout>) {
+	for (group, meta) in &layout.metadata {
+		if let Some(ref name) = meta.name {
+			data.set_name(group.clone(), name.clone());
+		}
+	}
+	if let Some(label) = layout.metadata
+		.get(&Reference::all())
+		.and_then(|meta| meta.format_label.clone())
+	{
+		data.set_label(Reference::all(), label);
+	}
+	data.layout = layout.clone();
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CustomLayout
+////////////////////////////////////////////////////////////////////////////////
+/// A `Layout` given by a layout description.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::format::custom::CustomLayout;
+/// use palette::sync::Shared;
+///
+/// let description = "\
+/// 	## A 256 color VGA palette with a smaller first line.\n\
+/// 	pages 1\n\
+/// 	lines 16\n\
+/// 	columns 16\n\
+/// 	meta 0/0/* columns 8\n\
+/// 	meta 0/*/* label \"VGA\"\n\
+/// 	meta */*/* name \"Game\"\n";
+///
+/// let layout: CustomLayout = description.parse().unwrap();
+/// let mut pal = Palette::new("", Format::Custom(Shared::new(layout)), true);
+///
+/// assert_eq!(pal.layout().column_count(0, 0), 8);
+/// assert_eq!(pal.layout().column_count(0, 1), 16);
+/// assert_eq!(pal.layout().page_label(0), Some("VGA".to_owned()));
+///
+/// // The layout is saved along with the palette.
+/// let mut buf = Vec::new();
+/// format::default::write_palette(&pal, &mut buf).unwrap();
+/// let loaded = format::default::read_palette(&mut &buf[..]).unwrap();
+/// assert_eq!(loaded.layout().column_count(0, 0), 8);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CustomLayout {
+	/// The default counts of the layout's pages, lines, and columns.
+	pub grid: GridLayout,
+
+	/// The metadata of the layout's groups. The line and column counts and 
+	/// labels of pages and lines are used by the layout, while names are 
+	/// given to new palettes as initial names.
+	pub metadata: HashMap<Reference, MetaData>,
+}
+
+
+impl CustomLayout {
+	/// Reads a layout description from the given buffer.
+	///
+	/// # Errors
+	///
+	/// Returns an `InvalidData` error if the description is malformed.
+	pub fn read<R>(in_buf: &mut R) -> io::Result<CustomLayout>
+		where R: io::Read
+	{
+		let mut layout = CustomLayout::default();
+
+		for (index, line) in io::BufReader::new(in_buf).lines().enumerate() {
+			let line = line?;
+			let invalid = |message: &str| invalid_line(index, message, &line);
+
+			let (keyword, rest) = split_word(&line);
+			match keyword {
+				"" => (),
+				_ if keyword.starts_with('#') => (),
+
+				"pages" => layout.grid.pages = rest.parse()
+					.ok()
+					.filter(|&count| count > 0)
+					.ok_or_else(|| invalid("invalid page count"))?,
+
+				"lines" => layout.grid.lines = rest.parse()
+					.ok()
+					.filter(|&count| count > 0)
+					.ok_or_else(|| invalid("invalid line count"))?,
+
+				"columns" => layout.grid.columns = rest.parse()
+					.ok()
+					.filter(|&count| count > 0)
+					.ok_or_else(|| invalid("invalid column count"))?,
+
+				"meta" => parse_metadata_line(rest, &mut layout.metadata)
+					.map_err(&invalid)?,
+
+				_ => return Err(invalid("unknown keyword")),
+			}
+		}
+		Ok(layout)
+	}
+
+	/// Writes the layout description to the given buffer.
+	pub fn write<W>(&self, out_buf: &mut W) -> io::Result<()>
+		where W: io::Write
+	{
+		for line in self.lines() {
+			writeln!(out_buf, "{}", line)?;
+		}
+		Ok(())
+	}
+
+	/// Returns the lines of the layout description.
+	pub(crate) fn lines(&self) -> Vec<String> {
+		let mut lines = vec![
+			format!("pages {}", self.grid.pages),
+			format!("lines {}", self.grid.lines),
+			format!("columns {}", self.grid.columns),
+		];
+		lines.extend(metadata_lines(&self.metadata));
+		lines
+	}
+
+	/// Returns the metadata of the given page.
+	fn page(&self, page: Page) -> Option<&MetaData> {
+		self.metadata.get(&Reference::page_of(&Address::new(page, 0, 0)))
+	}
+
+	/// Returns the metadata of the given line.
+	fn line(&self, page: Page, line: Line) -> Option<&MetaData> {
+		self.metadata.get(&Reference::line_of(&Address::new(page, line, 0)))
+	}
+}
+
+
+impl Layout for CustomLayout {
+	fn page_count(&self) -> Page {
+		self.grid.pages
+	}
+
+	fn line_count(&self, page: Page) -> Line {
+		self.page(page)
+			.and_then(|meta| meta.line_count)
+			.unwrap_or(self.grid.lines)
+	}
+
+	fn column_count(&self, page: Page, line: Line) -> Column {
+		self.line(page, line)
+			.and_then(|meta| meta.column_count)
+			.unwrap_or(self.grid.columns)
+	}
+
+	fn page_label(&self, page: Page) -> Option<String> {
+		self.page(page).and_then(|meta| meta.format_label.clone())
+	}
+
+	fn line_label(&self, page: Page, line: Line) -> Option<String> {
+		self.line(page, line).and_then(|meta| meta.format_label.clone())
+	}
+}
+
+
+impl FromStr for CustomLayout {
+	type Err = io::Error;
+
+	fn from_str(text: &str) -> io::Result<Self> {
+		CustomLayout::read(&mut text.as_bytes())
+	}
+}
@@ -28,9 +28,30 @@
 
 // Local imports.
 use ::Palette;
+use address::{Address, Reference};
+use data::MetaData;
+use expression::Expression;
+use format::{
+	invalid_line,
+	metadata_lines,
+	parse_metadata_line,
+	split_word,
+	Format,
+};
+use format::custom::CustomLayout;
+use sync::Shared;
 use operation::PaletteOperation;
 use result::Result;
 
+// Standard imports.
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+
+
+/// The version of the native palette file format.
+const FILE_VERSION: u32 = 1;
+
 
 
 /// Applies the given operation to the palette.
@@ -85,4 +106,229 @@ pub fn redo(palette: &mut Palette) -> Result<()> {
 	} else {
 		panic!("undo not supported")
 	}
-}
\ No newline at end of file
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Native file format
+////////////////////////////////////////////////////////////////////////////////
+// The native palette format is a line-based text format. Each line consists of
+// a keyword followed by its arguments:
+//
+// ```text
+// palette 1                     The file format version.
+// format zpl                    The palette format.
+// layout pages 2                A line of a custom format's layout description.
+// meta 0/*/* lines 12           The line count override for a group.
+// meta 0/3/* columns 8          The column count override for a group.
+// meta 0/*/* label "Level 0"    The format label for a group.
+// meta 0/*/* name "Main"        The user-provided name for a group.
+// define skin 0/3/*             A named reference.
+// cell 0:3:1 lighten(@skin, 5%) A cell and its expression.
+// ```
+//
+// The palette's layout is determined by its format. Blank lines are ignored.
+
+/// Writes the palette to the given buffer in the native text format.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::format::default::{read_palette, write_palette};
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+/// )).unwrap();
+/// pal.apply(Box::new(
+/// 	DefineName::new("ui.accent", Address::new(0, 0, 0).into())
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression("lighten(@ui.accent, 10%)".parse().unwrap())
+/// )).unwrap();
+///
+/// let mut buf = Vec::new();
+/// write_palette(&pal, &mut buf).unwrap();
+/// let loaded = read_palette(&mut &buf[..]).unwrap();
+///
+/// assert_eq!(loaded.len(), 2);
+/// assert_eq!(loaded.color(Address::new(0, 0, 1)), 
+/// 	pal.color(Address::new(0, 0, 1)));
+///
+/// let mut saved_again = Vec::new();
+/// write_palette(&loaded, &mut saved_again).unwrap();
+/// assert_eq!(buf, saved_again);
+/// ```
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> io::Result<()>
+	where W: io::Write
+{
+	let data = &palette.data;
+	writeln!(out_buf, "palette {}", FILE_VERSION)?;
+	writeln!(out_buf, "format {}", format_name(&palette.format))?;
+	if let Format::Custom(ref layout) = palette.format {
+		for line in layout.lines() {
+			writeln!(out_buf, "layout {}", line)?;
+		}
+	}
+
+	for line in metadata_lines(&data.metadata) {
+		writeln!(out_buf, "{}", line)?;
+	}
+
+	let mut names: Vec<_> = data.names.iter().collect();
+	names.sort_by(|a, b| a.0.cmp(b.0));
+	for (name, reference) in names {
+		writeln!(out_buf, "define {} {}", name, reference)?;
+	}
+
+	for (address, cell) in &data.cells {
+		writeln!(out_buf, "cell {} {}", address, *cell.borrow())?;
+	}
+	Ok(())
+}
+
+
+/// Reads a palette in the native text format from the given buffer.
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if the text is malformed, or if it defines
+/// an invalid or cyclic name or cell, or metadata which could not be set with
+/// `Data::set_metadata`.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::format::default::read_palette;
+///
+/// let text = "palette 1\n\
+/// 	define skin 0:0:0\n\
+/// 	cell 0:0:0 #0a141e\n\
+/// 	cell 0:0:1 @skin\n";
+/// let loaded = read_palette(&mut text.as_bytes()).unwrap();
+/// assert_eq!(loaded.color(Address::new(0, 0, 1)), 
+/// 	Some(Color::new(10, 20, 30)));
+///
+/// let text = "palette 1\ndefine a @b\ndefine b @a\n";
+/// assert!(read_palette(&mut text.as_bytes()).is_err());
+///
+/// let text = "palette 1\ndefine 0bad 0:0:0\n";
+/// assert!(read_palette(&mut text.as_bytes()).is_err());
+///
+/// // Column counts may only be given for lines.
+/// let text = "palette 1\nmeta 0/*/* columns 4\n";
+/// assert!(read_palette(&mut text.as_bytes()).is_err());
+///
+/// let text = "palette 1\nmeta 0/0/* columns 2\ncell 0:0:3 #000000\n";
+/// assert!(read_palette(&mut text.as_bytes()).is_err());
+/// ```
+pub fn read_palette<R>(in_buf: &mut R) -> io::Result<Palette>
+	where R: io::Read
+{
+	let mut format = Format::Default;
+	let mut layout = Vec::new();
+	let mut metadata: HashMap<Reference, MetaData> = HashMap::new();
+	let mut names = Vec::new();
+	let mut cells = Vec::new();
+
+	for (index, line) in io::BufReader::new(in_buf).lines().enumerate() {
+		let line = line?;
+		let invalid = |message: &str| invalid_line(index, message, &line);
+
+		let (keyword, rest) = split_word(&line);
+		match keyword {
+			"" => (),
+
+			"palette" => if rest.parse::<u32>().ok() != Some(FILE_VERSION) {
+				return Err(invalid("unsupported file version"));
+			},
+
+			"format" => format = parse_format(rest)
+				.ok_or_else(|| invalid("unknown format"))?,
+
+			"layout" => layout.push(rest.to_owned()),
+
+			"meta" => parse_metadata_line(rest, &mut metadata)
+				.map_err(&invalid)?,
+
+			"define" => {
+				let (name, reference) = split_word(rest);
+				let reference: Reference = reference.parse()
+					.map_err(|_| invalid("invalid reference"))?;
+				names.push((name.to_owned(), reference));
+			},
+
+			"cell" => {
+				let (address, expression) = split_word(rest);
+				let address: Address = address.parse()
+					.map_err(|_| invalid("invalid address"))?;
+				let expression = if expression.is_empty() {
+					Expression::Empty
+				} else {
+					expression.parse()
+						.map_err(|_| invalid("invalid expression"))?
+				};
+				cells.push((address, expression));
+			},
+
+			_ => return Err(invalid("unknown keyword")),
+		}
+	}
+
+	if let Format::Custom(_) = format {
+		let layout: CustomLayout = layout.join("\n").parse()?;
+		format = Format::Custom(Shared::new(layout));
+	} else if !layout.is_empty() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData, 
+			"layout given for a format without a custom layout"));
+	}
+
+	let mut palette = Palette::new("", format, true);
+	{
+		let data = &mut palette.data;
+		data.metadata.remove(&Reference::all());
+
+		let to_io_error = |error: ::result::Error| io::Error::new(
+			io::ErrorKind::InvalidData, 
+			error.to_string());
+		for (group, meta) in metadata {
+			data.set_metadata(group, Some(meta)).map_err(&to_io_error)?;
+		}
+		for (name, reference) in names {
+			data.define_name(name, reference).map_err(&to_io_error)?;
+		}
+		for &(address, _) in &cells {
+			data.create_cell(address).map_err(&to_io_error)?;
+		}
+		for (address, expression) in cells {
+			data.set_expression(address, expression).map_err(&to_io_error)?;
+		}
+	}
+	Ok(palette)
+}
+
+
+/// Returns the name of the given format in the native file format.
+fn format_name(format: &Format) -> &'static str {
+	match *format {
+		Format::Default => "default",
+		Format::Zpl => "zpl",
+		Format::Custom(_) => "custom",
+	}
+}
+
+
+/// Parses a format name from the native file format. The layout of a custom 
+/// format is read separately.
+fn parse_format(name: &str) -> Option<Format> {
+	match name {
+		"default" => Some(Format::Default),
+		"zpl" => Some(Format::Zpl),
+		"custom" => Some(Format::Custom(Default::default())),
+		_ => None,
+	}
+}
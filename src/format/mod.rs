@@ -31,16 +31,20 @@
 #[warn(missing_docs)]
 pub mod zpl;
 #[warn(missing_docs)]
+pub mod custom;
+#[warn(missing_docs)]
 pub mod default;
 
 // Module imports.
 use Palette;
 use address::Reference;
-use data::Data;
+use data::{Data, MetaData};
 use operation::PaletteOperation;
 use result::Result;
+use sync::Shared;
 
 // Standard imports.
+use std::collections::HashMap;
 use std::io;
 
 
@@ -48,7 +52,7 @@ use std::io;
 // Format
 ////////////////////////////////////////////////////////////////////////////////
 /// An enum of the supported palette formats.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Format {
 	/// The default palette format; provides no special behaviors or 
 	/// restrictions.
@@ -58,45 +62,27 @@ pub enum Format {
 	/// lines per page, for 211 pages. The names of lines and pages are 
 	/// auto-generated.
 	Zpl,
+
+	/// A user-defined format. The layout, labels, and names of the palette are
+	/// given by a `CustomLayout`, which is usually read from a layout 
+	/// description file.
+	Custom(Shared<custom::CustomLayout>),
 }
 
 #[cfg_attr(feature = "cargo-clippy", allow(single_match))]
 impl Format {
 	/// Called when a new palette is created. Initializes the palette data.
-	pub fn initialize(self, data: &mut Data)  {
-		match self {
+	pub fn initialize(&self, data: &mut Data)  {
+		match *self {
 			Format::Zpl => zpl::initialize(data),
-			_ => (),
-		}
-	}
-
-	/// Called when a new page is created.
-	pub fn prepare_new_page(
-		self, data: 
-		&mut Data, 
-		group: &Reference) 
-	{
-		match self {
-			Format::Zpl => zpl::prepare_new_page(data, group),
-			_ => (),
-		}
-	}
-
-	/// Called when a new line is created.
-	pub fn prepare_new_line(
-		self, 
-		data: &mut Data, 
-		group: &Reference) 
-	{
-		match self {
-			Format::Zpl => zpl::prepare_new_line(data, group),
+			Format::Custom(ref layout) => custom::initialize(data, layout),
 			_ => (),
 		}
 	}
 
 	/// Applies the given operation to the palette. 
 	pub fn apply_operation(
-		self, 
+		&self, 
 		palette: &mut Palette, 
 		operation: Box<PaletteOperation>) 
 		-> Result<()>
@@ -105,18 +91,19 @@ impl Format {
 	}
 
 	/// Reverses the most recently applied operation.
-	pub fn undo(self, palette: &mut Palette) -> Result<()> {
+	pub fn undo(&self, palette: &mut Palette) -> Result<()> {
 		default::undo(palette)
 	}
 
 	/// Reverses the most recently applied undo operation.
-	pub fn redo(self, palette: &mut Palette) -> Result<()> {
+	pub fn redo(&self, palette: &mut Palette) -> Result<()> {
 		default::redo(palette)
 	}
 
 	/// Writes the palette to the given buffer.
 	#[allow(unused_variables)]
-	pub fn write_palette<W>(self, palette: &Palette, out_buf: &mut W) -> io::Result<()> 
+	pub fn write_palette<W>(&self, palette: &Palette, out_buf: &mut W) 
+		-> io::Result<()> 
 		where W: io::Write
 	{
 		unimplemented!()
@@ -124,7 +111,7 @@ impl Format {
 
 	/// Reads a palette from the given buffer.
 	#[allow(unused_variables)]
-	pub fn read_palette<R>(self, in_buf: &mut R) -> io::Result<()> 
+	pub fn read_palette<R>(&self, in_buf: &mut R) -> io::Result<()> 
 		where R: io::Read
 	{
 		unimplemented!()
@@ -132,3 +119,130 @@ impl Format {
 }
 
 
+
+////////////////////////////////////////////////////////////////////////////////
+// Text format utilities
+////////////////////////////////////////////////////////////////////////////////
+/// Returns the lines describing the given metadata, in the form 
+/// `meta <group> <field> <value>`. The lines are sorted by group so that the
+/// output is deterministic.
+fn metadata_lines(metadata: &HashMap<Reference, MetaData>) -> Vec<String> {
+	let mut groups: Vec<_> = metadata
+		.iter()
+		.map(|(group, meta)| (group.to_string(), meta))
+		.collect();
+	groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+	let mut lines = Vec::new();
+	for (group, meta) in groups {
+		if let Some(line_count) = meta.line_count {
+			lines.push(format!("meta {} lines {}", group, line_count));
+		}
+		if let Some(column_count) = meta.column_count {
+			lines.push(format!("meta {} columns {}", group, column_count));
+		}
+		if let Some(ref label) = meta.format_label {
+			lines.push(format!("meta {} label {}", group, quote(label)));
+		}
+		if let Some(ref name) = meta.name {
+			lines.push(format!("meta {} name {}", group, quote(name)));
+		}
+	}
+	lines
+}
+
+
+/// Parses the arguments of a `meta <group> <field> <value>` line into the 
+/// given metadata. Returns a description of the problem if the arguments are 
+/// invalid.
+fn parse_metadata_line(
+	text: &str, 
+	metadata: &mut HashMap<Reference, MetaData>)
+	-> ::std::result::Result<(), &'static str>
+{
+	let (group, rest) = split_word(text);
+	let (field, value) = split_word(rest);
+	let group: Reference = group.parse().map_err(|_| "invalid group")?;
+	let meta = metadata.entry(group).or_insert_with(Default::default);
+	match field {
+		"lines" => meta.line_count = Some(value.parse()
+			.ok()
+			.filter(|&count| count > 0)
+			.ok_or("invalid line count")?),
+		"columns" => meta.column_count = Some(value.parse()
+			.ok()
+			.filter(|&count| count > 0)
+			.ok_or("invalid column count")?),
+		"label" => meta.format_label = Some(unquote(value)
+			.ok_or("invalid label")?),
+		"name" => meta.name = Some(unquote(value)
+			.ok_or("invalid name")?),
+		_ => return Err("unknown metadata field"),
+	}
+	Ok(())
+}
+
+
+/// Returns an `InvalidData` error for the given line of a text file.
+fn invalid_line(index: usize, message: &str, line: &str) -> io::Error {
+	io::Error::new(
+		io::ErrorKind::InvalidData, 
+		format!("line {}: {}: {}", index + 1, message, line))
+}
+
+
+/// Splits the first whitespace-delimited word from the given text. Returns the
+/// word and the remaining text with surrounding whitespace removed.
+fn split_word(text: &str) -> (&str, &str) {
+	let text = text.trim();
+	match text.find(char::is_whitespace) {
+		Some(position) => (&text[..position], text[position..].trim()),
+		None => (text, ""),
+	}
+}
+
+
+/// Returns the given text as a quoted string, escaping any quotes, 
+/// backslashes, and line breaks.
+fn quote(text: &str) -> String {
+	let mut quoted = String::with_capacity(text.len() + 2);
+	quoted.push('"');
+	for c in text.chars() {
+		match c {
+			'"'  => quoted.push_str("\\\""),
+			'\\' => quoted.push_str("\\\\"),
+			'\n' => quoted.push_str("\\n"),
+			'\r' => quoted.push_str("\\r"),
+			_    => quoted.push(c),
+		}
+	}
+	quoted.push('"');
+	quoted
+}
+
+
+/// Parses a quoted string produced by `quote`, returning `None` if it is 
+/// invalid.
+fn unquote(text: &str) -> Option<String> {
+	let text = text.trim();
+	if text.len() < 2 || !text.starts_with('"') || !text.ends_with('"') {
+		return None;
+	}
+
+	let mut unquoted = String::with_capacity(text.len());
+	let mut chars = text[1..text.len() - 1].chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => match chars.next() {
+				Some('"')  => unquoted.push('"'),
+				Some('\\') => unquoted.push('\\'),
+				Some('n')  => unquoted.push('\n'),
+				Some('r')  => unquoted.push('\r'),
+				_          => return None,
+			},
+			'"' => return None,
+			_ => unquoted.push(c),
+		}
+	}
+	Some(unquoted)
+}
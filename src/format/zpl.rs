@@ -29,9 +29,12 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 use address::{
+	Address,
 	Reference,
 	Page, Line, Column};
 use data::Data;
+use layout::Layout;
+use sync::Shared;
 
 
 const ZPL_COLOR_DEPTH_SCALE: f32 = 0.25;
@@ -76,6 +79,7 @@ const ZPL_FOOTER_E : [u8;36] = [
 
 const ZPL_PAGE_LIMIT: Page =  0x203;
 const ZPL_DEFAULT_LINE_LIMIT: Line =  16;
+const ZPL_MAIN_LINE_LIMIT: Line =  14;
 const ZPL_DEFAULT_COLUMN_LIMIT: Column =  16;
 
 const MAIN_PAGE_LIMIT: Page = 0;
@@ -102,50 +106,78 @@ fn level_label(line: Line) -> String {
 /// Called when a new palette is created. Initializes the palette data.
 pub fn initialize(data: &mut Data) {
 	data.set_label(Reference::all(), "ZplPalette 1.0.0");
-	data.maximum_page_count = ZPL_PAGE_LIMIT;
-	data.default_line_count = ZPL_DEFAULT_LINE_LIMIT;
-	data.default_column_count = ZPL_DEFAULT_COLUMN_LIMIT;
-	data.prepare_new_page = prepare_new_page;
-	data.prepare_new_line = prepare_new_line;
+	data.set_name(Reference::page_of(&Address::new(0, 0, 0)), "Main");
+	data.layout = Shared::new(ZplLayout);
+	data.cells.make_dense();
 }
 
-	
-/// The function to call when a new page is created.
+
+////////////////////////////////////////////////////////////////////////////////
+// ZplLayout
+////////////////////////////////////////////////////////////////////////////////
+/// The `Layout` of a ZPL palette. The main page has 14 lines, and all other 
+/// pages have 16 lines. Every line has 16 columns.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::Data;
+///
+/// let mut dat: Data = Default::default();
+/// Format::Zpl.initialize(&mut dat);
+///
+/// let main = Reference::page_of(&Address::new(0, 0, 0));
+/// assert_eq!(dat.name(&main), Some("Main"));
+/// assert_eq!(dat.label(&main).unwrap(), "Level 0");
+/// assert_eq!(dat.label(&Reference::page_of(&Address::new(3, 0, 0))).unwrap(),
+/// 	"Level 3");
+/// assert_eq!(dat.label(&Reference::line_of(&Address::new(0, 2, 0))).unwrap(),
+/// 	"Main CSET 2");
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZplLayout;
+
+
 #[cfg_attr(feature = "cargo-clippy", allow(absurd_extreme_comparisons))]
-pub fn prepare_new_page(data: &mut Data, group: &Reference) {
-	let page = group.page().expect("prepare page group with page reference");
-	if page <= MAIN_PAGE_LIMIT {
-		data.set_name(group.clone(), "Main");
-		data.set_label(group.clone(), "Level 0");
-		data.set_line_count(group.clone(), 14);
-	} else if page <= LEVEL_PAGE_LIMIT {
-		data.set_label(group.clone(), format!("Level {}", page));
-	} else {
-		data.set_label(group.clone(), format!("Sprite Page {}", page));
+impl Layout for ZplLayout {
+	fn page_count(&self) -> Page {
+		ZPL_PAGE_LIMIT
+	}
+
+	fn line_count(&self, page: Page) -> Line {
+		if page <= MAIN_PAGE_LIMIT {
+			ZPL_MAIN_LINE_LIMIT
+		} else {
+			ZPL_DEFAULT_LINE_LIMIT
+		}
 	}
-}
 
+	fn column_count(&self, _: Page, _: Line) -> Column {
+		ZPL_DEFAULT_COLUMN_LIMIT
+	}
 
-/// The function to call when a new line is created.
-#[cfg_attr(feature = "cargo-clippy", allow(absurd_extreme_comparisons))]
-pub fn prepare_new_line(data: &mut Data, group: &Reference) {
-	let page = group.page().expect("prepare line group with page reference");
-	let line = group.line().expect("prepare line group with line reference");
-
-	if page <= MAIN_PAGE_LIMIT {
-		data.set_label(group.clone(), format!("Main CSET {}", line));
-	} else if page <= LEVEL_PAGE_LIMIT {
-		data.set_label(group.clone(), 
+	fn page_label(&self, page: Page) -> Option<String> {
+		Some(if page <= MAIN_PAGE_LIMIT {
+			"Level 0".to_owned()
+		} else if page <= LEVEL_PAGE_LIMIT {
+			format!("Level {}", page)
+		} else {
+			format!("Sprite Page {}", page)
+		})
+	}
+
+	fn line_label(&self, page: Page, line: Line) -> Option<String> {
+		Some(if page <= MAIN_PAGE_LIMIT {
+			format!("Main CSET {}", line)
+		} else if page <= LEVEL_PAGE_LIMIT {
 			level_label(line)
-		);
-	} else {
-		data.set_label(group.clone(), 
+		} else {
 			format!("Sprite CSET {}", page as usize 
 				- LEVEL_PAGE_LIMIT as usize + line as usize
 			)
-		);
+		})
 	}
-	
 }
 
 
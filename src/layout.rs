@@ -0,0 +1,184 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines the `Layout` trait, which describes how a palette's cells are 
+//! arranged into pages, lines, and columns.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{
+	Page, Line, Column,
+	PAGE_MAX, LINE_MAX, COLUMN_MAX,
+};
+use sync::Threadsafe;
+
+// Standard imports.
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Layout
+////////////////////////////////////////////////////////////////////////////////
+/// Describes the number of pages, lines, and columns in a palette, along with
+/// the labels of its pages and lines. Layouts are queried on demand, so they 
+/// may carry their own configuration and compute their answers as needed. The
+/// line and column counts of individual groups may still be overridden in the
+/// palette's metadata.
+///
+/// # Example
+///
+/// A layout for the NES, which has four background and four sprite palettes of
+/// four colors each:
+///
+/// ```rust
+/// use palette::*;
+/// use palette::address::{Page, Line, Column};
+/// use palette::layout::Layout;
+/// use palette::operation::*;
+/// use palette::result::Error;
+/// use palette::sync::Shared;
+///
+/// #[derive(Debug)]
+/// struct NesLayout;
+///
+/// impl Layout for NesLayout {
+/// 	fn page_count(&self) -> Page {2}
+/// 	fn line_count(&self, _: Page) -> Line {4}
+/// 	fn column_count(&self, _: Page, _: Line) -> Column {4}
+///
+/// 	fn page_label(&self, page: Page) -> Option<String> {
+/// 		Some(if page == 0 {"Background"} else {"Sprite"}.to_owned())
+/// 	}
+/// }
+///
+/// let mut pal = Palette::new("NES", Format::Default, true);
+/// pal.set_layout(Shared::new(NesLayout));
+///
+/// let black = Expression::Color(Color::new(0, 0, 0));
+/// pal.apply(Box::new(Repeat::new(Box::new(InsertCell::new()
+/// 	.with_expression(black.clone())
+/// )).repeat(32))).unwrap();
+/// assert_eq!(pal.len(), 32);
+/// assert_eq!(pal.color(Address::new(1, 3, 3)), Some(Color::new(0, 0, 0)));
+///
+/// match pal.apply(Box::new(InsertCell::new().with_expression(black))) {
+/// 	Err(Error::MaxCellLimitExceeded) => (),
+/// 	result => panic!("unexpected result {:?}", result),
+/// }
+/// ```
+pub trait Layout: fmt::Debug + Threadsafe {
+	/// Returns the number of pages in the palette.
+	fn page_count(&self) -> Page;
+
+	/// Returns the number of lines in the given page.
+	fn line_count(&self, page: Page) -> Line;
+
+	/// Returns the number of columns in the given line.
+	fn column_count(&self, page: Page, line: Line) -> Column;
+
+	/// Returns the label of the given page, or `None` if it has no label.
+	#[allow(unused_variables)]
+	fn page_label(&self, page: Page) -> Option<String> {
+		None
+	}
+
+	/// Returns the label of the given line, or `None` if it has no label.
+	#[allow(unused_variables)]
+	fn line_label(&self, page: Page, line: Line) -> Option<String> {
+		None
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// GridLayout
+////////////////////////////////////////////////////////////////////////////////
+/// A `Layout` in which every page has the same number of lines and every line
+/// has the same number of columns. The default grid is as large as an 
+/// `Address` allows.
+///
+/// # Example
+///
+/// A layout for a 256-color VGA palette:
+///
+/// ```rust
+/// use palette::Address;
+/// use palette::data::Data;
+/// use palette::layout::GridLayout;
+/// use palette::sync::Shared;
+///
+/// let mut dat: Data = Default::default();
+/// dat.layout = Shared::new(GridLayout::new(1, 16, 16));
+///
+/// assert_eq!(dat.address_of(255).unwrap(), Address::new(0, 15, 15));
+/// assert!(dat.address_of(256).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridLayout {
+	/// The number of pages in the grid.
+	pub pages: Page,
+
+	/// The number of lines in each page.
+	pub lines: Line,
+
+	/// The number of columns in each line.
+	pub columns: Column,
+}
+
+
+impl GridLayout {
+	/// Creates a new `GridLayout` with the given dimensions.
+	pub fn new(pages: Page, lines: Line, columns: Column) -> GridLayout {
+		GridLayout {
+			pages: pages,
+			lines: lines,
+			columns: columns,
+		}
+	}
+}
+
+
+impl Layout for GridLayout {
+	fn page_count(&self) -> Page {
+		self.pages
+	}
+
+	fn line_count(&self, _: Page) -> Line {
+		self.lines
+	}
+
+	fn column_count(&self, _: Page, _: Line) -> Column {
+		self.columns
+	}
+}
+
+
+impl Default for GridLayout {
+	fn default() -> Self {
+		GridLayout::new(PAGE_MAX, LINE_MAX, COLUMN_MAX)
+	}
+}
@@ -49,16 +49,30 @@ pub mod address;
 #[warn(missing_docs)]
 pub mod cell;
 #[warn(missing_docs)]
+pub mod colorspace;
+#[warn(missing_docs)]
 pub mod data;
 #[warn(missing_docs)]
+pub mod dependency;
+#[warn(missing_docs)]
+pub mod diff;
+#[warn(missing_docs)]
 pub mod expression;
 #[warn(missing_docs)]
 pub mod format;
 #[warn(missing_docs)]
+pub mod layout;
+#[warn(missing_docs)]
+pub mod merge;
+#[warn(missing_docs)]
 pub mod operation;
 #[warn(missing_docs)]
 pub mod result;
 #[warn(missing_docs)]
+pub mod storage;
+#[warn(missing_docs)]
+pub mod sync;
+#[warn(missing_docs)]
 pub mod utilities;
 
 
@@ -76,9 +90,11 @@ pub use format::Format;
 
 
 // Local imports.
-use data::Data;
+use data::{Data, Snapshot};
+use layout::Layout;
 use operation::{PaletteOperation, OperationHistory};
 use result::Result;
+use sync::Shared;
 
 // Standard imports.
 use std::fmt;
@@ -118,7 +134,7 @@ impl Palette {
 		};
 		
 		pal.data.set_name(Reference::all(), name.into());
-		format.initialize(&mut pal.data);
+		pal.format.initialize(&mut pal.data);
 		pal
 	}
 
@@ -152,7 +168,91 @@ impl Palette {
 
 	/// Returns the color at the given address, or None if the cell is empty.
 	pub fn color(&self, address: Address) -> Option<Color> {
-		self.data.cell(address).and_then(|cell| cell.color())
+		self.data.color(address)
+	}
+
+	/// Returns the `Layout` of the `Palette`.
+	pub fn layout(&self) -> &Layout {
+		&*self.data.layout
+	}
+
+	/// Replaces the `Layout` of the `Palette`. Existing cells which lie outside
+	/// of the new layout are kept, but no new cells may be created there.
+	pub fn set_layout(&mut self, layout: Shared<Layout>) {
+		self.data.layout = layout;
+	}
+
+	/// Returns a `Snapshot` of the `Palette`'s current cells, names, and 
+	/// metadata.
+	pub fn snapshot(&self) -> Snapshot {
+		self.data.snapshot()
+	}
+
+	/// Returns a `Snapshot` of the `Palette` as it would be after applying the 
+	/// given operation, without changing the `Palette` or its history. The 
+	/// operation is applied through the `Palette`'s format, as in `apply`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::operation::*;
+	/// 
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// pal.apply(Box::new(InsertCell::new()
+	/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+	/// )).unwrap();
+	///
+	/// let preview = pal.preview(Box::new(InsertCell::new()
+	/// 	.located_at(Address::new(0, 0, 0))
+	/// 	.overwrite(true)
+	/// 	.with_expression(Expression::Color(Color::new(0, 0, 0)))
+	/// )).unwrap();
+	///
+	/// assert_eq!(preview.color(Address::new(0, 0, 0)), 
+	/// 	Some(Color::new(0, 0, 0)));
+	/// assert_eq!(pal.color(Address::new(0, 0, 0)), 
+	/// 	Some(Color::new(12, 50, 78)));
+	/// assert_eq!(pal.history_len(), (1, 0));
+	/// ```
+	pub fn preview(&self, operation: Box<PaletteOperation>)
+		-> Result<Snapshot>
+	{
+		let mut preview = Palette {
+			data: self.data.share(),
+			operation_history: None,
+			format: self.format.clone(),
+		};
+		self.format.apply_operation(&mut preview, operation)?;
+		Ok(preview.data.snapshot())
+	}
+
+	/// Replaces the `Palette`'s cells, names, and metadata with those of the 
+	/// given `Snapshot`. This cannot be undone, and the operation history is 
+	/// cleared, so neither the restore nor any earlier operation can be undone
+	/// or redone afterwards.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::operation::*;
+	/// 
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// let empty = pal.snapshot();
+	/// pal.apply(Box::new(InsertCell::new()
+	/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+	/// )).unwrap();
+	///
+	/// pal.restore(&empty);
+	/// assert_eq!(pal.len(), 0);
+	/// assert_eq!(pal.history_len(), (0, 0));
+	/// ```
+	pub fn restore(&mut self, snapshot: &Snapshot) {
+		self.data = snapshot.to_data();
+		if let Some(ref mut history) = self.operation_history {
+			*history = Default::default();
+		}
 	}
 
 
@@ -166,19 +266,19 @@ impl Palette {
 		operation: Box<PaletteOperation>)
 		-> Result<()> 
 	{
-		self.format.apply_operation(self, operation)
+		self.format.clone().apply_operation(self, operation)
 	}
 
 	/// Reverses the most recently applied operation.
 	#[allow(unused_variables)]
 	pub fn undo(&mut self) -> Result<()> {
-		self.format.undo(self)
+		self.format.clone().undo(self)
 	}
 
 	/// Reverses the most recently applied undo operation.
 	#[allow(unused_variables)]
 	pub fn redo(&mut self) -> Result<()> {
-		self.format.redo(self)
+		self.format.clone().redo(self)
 	}
 }
 
@@ -0,0 +1,557 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides three-way merging of palettes.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference};
+use data::{Data, MetaData};
+use diff::{CellDiff, Diff, MetaDataDiff, NameDiff};
+use expression::Expression;
+use operation::Sequence;
+
+// Standard imports.
+use std::collections::BTreeSet;
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Merge
+////////////////////////////////////////////////////////////////////////////////
+/// The result of merging two versions of a palette which share a common 
+/// ancestor.
+///
+/// The merge is expressed as changes to be made to 'our' version: every cell,
+/// name, or metadata entry which was changed only in 'their' version is 
+/// taken from theirs, and every entry which was changed differently in both 
+/// versions is reported as a `Conflict`.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::merge::{Merge, Side};
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(
+/// 	Repeat::new(Box::new(InsertCell::new()
+/// 		.with_expression(Expression::Color(Color::new(0, 0, 0)))
+/// 	)).repeat(3)
+/// )).unwrap();
+/// let base = pal.snapshot();
+///
+/// // Their version changes two cells.
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(Address::new(0, 0, 1))
+/// 	.overwrite(true)
+/// 	.with_expression(Expression::Color(Color::new(0, 0xff, 0)))
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(Address::new(0, 0, 2))
+/// 	.overwrite(true)
+/// 	.with_expression(Expression::Color(Color::new(0, 0, 0xff)))
+/// )).unwrap();
+/// let theirs = pal.snapshot();
+///
+/// // Our version changes one of the same cells.
+/// pal.restore(&base);
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(Address::new(0, 0, 2))
+/// 	.overwrite(true)
+/// 	.with_expression(Expression::Color(Color::new(0xff, 0, 0)))
+/// )).unwrap();
+///
+/// let merge = Merge::new(&base, &pal.snapshot(), &theirs);
+/// assert_eq!(merge.conflicts.len(), 1);
+/// assert_eq!(merge.conflicts[0].to_string(), 
+/// 	"! cell 0:0:2: ours #ff0000, theirs #0000ff, base #000000");
+///
+/// pal.apply(Box::new(merge.to_operations())).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(0, 0xff, 0)));
+/// assert_eq!(pal.color(Address::new(0, 0, 2)), Some(Color::new(0xff, 0, 0)));
+///
+/// // Resolve the conflict in favor of their version.
+/// pal.apply(Box::new(merge.conflicts[0].resolve(Side::Theirs))).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 2)), Some(Color::new(0, 0, 0xff)));
+///
+/// // The merge can be undone like any other operation.
+/// pal.undo().unwrap();
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(0, 0, 0)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Merge {
+	/// The non-conflicting changes to apply to our version.
+	pub changes: Diff,
+
+	/// The changes which conflict between the two versions.
+	pub conflicts: Vec<Conflict>,
+}
+
+
+impl Merge {
+	/// Merges the `theirs` `Data` into the `ours` `Data`, using `base` as 
+	/// their common ancestor.
+	pub fn new(base: &Data, ours: &Data, theirs: &Data) -> Merge {
+		let mut merge = Merge::default();
+		merge.merge_cells(base, ours, theirs);
+		merge.merge_names(base, ours, theirs);
+		merge.merge_metadata(base, ours, theirs);
+		merge
+	}
+
+	/// Returns whether the merge has any conflicts.
+	pub fn has_conflicts(&self) -> bool {
+		!self.conflicts.is_empty()
+	}
+
+	/// Returns a `Sequence` of operations which will apply the non-conflicting
+	/// changes to our version. Applying the sequence may still fail if the 
+	/// combined changes would create a cyclic reference.
+	pub fn to_operations(&self) -> Sequence {
+		self.changes.to_operations()
+	}
+
+	/// Merges the cell expressions of the given `Data`.
+	fn merge_cells(&mut self, base: &Data, ours: &Data, theirs: &Data) {
+		let mut addresses: BTreeSet<Address> = BTreeSet::new();
+		addresses.extend(base.cells.keys());
+		addresses.extend(ours.cells.keys());
+		addresses.extend(theirs.cells.keys());
+
+		for address in addresses {
+			let versions = (
+				expression_at(base, address), 
+				expression_at(ours, address), 
+				expression_at(theirs, address));
+
+			match merge_versions(versions) {
+				Merged::Ours => (),
+				Merged::Theirs(ours, theirs) => self.changes.cells.push(
+					cell_diff(address, ours, theirs)),
+				Merged::Conflict(base, ours, theirs) => self.conflicts.push(
+					Conflict::Cell {
+						address: address,
+						base: base,
+						ours: ours,
+						theirs: theirs,
+					}),
+			}
+		}
+	}
+
+	/// Merges the names of the given `Data`.
+	fn merge_names(&mut self, base: &Data, ours: &Data, theirs: &Data) {
+		let names: BTreeSet<_> = base.names.keys()
+			.chain(ours.names.keys())
+			.chain(theirs.names.keys())
+			.cloned()
+			.collect();
+
+		for name in names {
+			let versions = (
+				base.names.get(&name).cloned(), 
+				ours.names.get(&name).cloned(), 
+				theirs.names.get(&name).cloned());
+
+			match merge_versions(versions) {
+				Merged::Ours => (),
+				Merged::Theirs(ours, theirs) => self.changes.names.push(
+					name_diff(name, ours, theirs)),
+				Merged::Conflict(base, ours, theirs) => self.conflicts.push(
+					Conflict::Name {
+						name: name,
+						base: base,
+						ours: ours,
+						theirs: theirs,
+					}),
+			}
+		}
+	}
+
+	/// Merges the metadata of the given `Data`. Each field of a group's 
+	/// metadata is merged separately, so that changes to different fields in 
+	/// both versions do not conflict. A group with no fields set is treated 
+	/// as having no metadata if it has none in either version.
+	fn merge_metadata(&mut self, base: &Data, ours: &Data, theirs: &Data) {
+		let mut groups: Vec<_> = base.metadata.keys()
+			.chain(ours.metadata.keys())
+			.chain(theirs.metadata.keys())
+			.cloned()
+			.collect();
+		groups.sort_by_key(|group| group.to_string());
+		groups.dedup();
+
+		let empty = MetaData::default();
+		for group in groups {
+			let base = base.metadata.get(&group);
+			let ours = ours.metadata.get(&group);
+			let theirs = theirs.metadata.get(&group);
+			if ours == theirs || theirs == base {continue;}
+
+			// `merged` takes their version of every non-conflicting field, 
+			// and `resolved` also takes their version of conflicting fields.
+			let base_fields = base.unwrap_or(&empty);
+			let their_fields = theirs.unwrap_or(&empty);
+			let mut merged = ours.unwrap_or(&empty).clone();
+			let mut resolved = merged.clone();
+			let conflicted = 
+				merge_field(
+					&mut merged.format_label, 
+					&mut resolved.format_label, 
+					&base_fields.format_label, 
+					&their_fields.format_label) |
+				merge_field(
+					&mut merged.name, 
+					&mut resolved.name, 
+					&base_fields.name, 
+					&their_fields.name) |
+				merge_field(
+					&mut merged.line_count, 
+					&mut resolved.line_count, 
+					&base_fields.line_count, 
+					&their_fields.line_count) |
+				merge_field(
+					&mut merged.column_count, 
+					&mut resolved.column_count, 
+					&base_fields.column_count, 
+					&their_fields.column_count);
+
+			let entry = |fields: MetaData| {
+				if fields == empty && (ours.is_none() || theirs.is_none()) {
+					None
+				} else {
+					Some(fields)
+				}
+			};
+			let merged = entry(merged);
+			let resolved = entry(resolved);
+
+			if merged.as_ref() != ours {
+				self.changes.metadata.push(MetaDataDiff {
+					group: group.clone(),
+					old: ours.cloned(),
+					new: merged.clone(),
+				});
+			}
+			if conflicted {
+				self.conflicts.push(Conflict::MetaData {
+					group: group,
+					base: base.cloned(),
+					ours: merged,
+					theirs: resolved,
+				});
+			}
+		}
+	}
+}
+
+
+impl fmt::Display for Merge {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.changes)?;
+		for conflict in &self.conflicts {
+			writeln!(f, "{}", conflict)?;
+		}
+		Ok(())
+	}
+}
+
+
+/// The outcome of merging a single value.
+enum Merged<T> {
+	/// Our version of the value is kept.
+	Ours,
+	/// Our version of the value is replaced by theirs.
+	Theirs(Option<T>, Option<T>),
+	/// The value was changed differently in both versions.
+	Conflict(Option<T>, Option<T>, Option<T>),
+}
+
+
+/// Merges the base, our, and their versions of a value.
+fn merge_versions<T>(versions: (Option<T>, Option<T>, Option<T>)) 
+	-> Merged<T>
+	where T: PartialEq
+{
+	let (base, ours, theirs) = versions;
+	if ours == theirs || theirs == base {
+		Merged::Ours
+	} else if ours == base {
+		Merged::Theirs(ours, theirs)
+	} else {
+		Merged::Conflict(base, ours, theirs)
+	}
+}
+
+
+/// Merges their version of a metadata field into `merged` and `resolved`, 
+/// which both start as our version. Returns whether the field conflicts, in 
+/// which case only `resolved` takes their version.
+fn merge_field<T>(
+	merged: &mut Option<T>, 
+	resolved: &mut Option<T>, 
+	base: &Option<T>, 
+	theirs: &Option<T>)
+	-> bool
+	where T: PartialEq + Clone
+{
+	if merged == theirs || theirs == base {
+		false
+	} else if merged == base {
+		*merged = theirs.clone();
+		*resolved = theirs.clone();
+		false
+	} else {
+		*resolved = theirs.clone();
+		true
+	}
+}
+
+
+/// Returns the expression of the cell at the given address, if there is one.
+fn expression_at(data: &Data, address: Address) -> Option<Expression> {
+	data.cells.get(&address).map(|cell| cell.borrow().clone())
+}
+
+
+/// Returns the `CellDiff` which replaces one version of a cell with another.
+fn cell_diff(
+	address: Address, 
+	old: Option<Expression>, 
+	new: Option<Expression>) 
+	-> CellDiff
+{
+	match (old, new) {
+		(Some(old), Some(new)) => CellDiff::Changed(address, old, new),
+		(None, Some(new)) => CellDiff::Added(address, new),
+		(Some(old), None) => CellDiff::Removed(address, old),
+		(None, None) => unreachable!(),
+	}
+}
+
+
+/// Returns the `NameDiff` which replaces one version of a name with another.
+fn name_diff(
+	name: String, 
+	old: Option<Reference>, 
+	new: Option<Reference>) 
+	-> NameDiff
+{
+	match (old, new) {
+		(Some(old), Some(new)) => NameDiff::Changed(name, old, new),
+		(None, Some(new)) => NameDiff::Defined(name, new),
+		(Some(old), None) => NameDiff::Removed(name, old),
+		(None, None) => unreachable!(),
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Side
+////////////////////////////////////////////////////////////////////////////////
+/// Selects one of the two merged versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+	/// Our version, into which the changes are merged.
+	Ours,
+	/// Their version, from which the changes are merged.
+	Theirs,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Conflict
+////////////////////////////////////////////////////////////////////////////////
+/// A value which was changed differently in both merged versions. Each version
+/// is `None` if the value does not exist in that version.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+	/// Conflicting changes to a cell's expression.
+	Cell {
+		/// The address of the cell.
+		address: Address,
+		/// The expression in the common ancestor.
+		base: Option<Expression>,
+		/// The expression in our version.
+		ours: Option<Expression>,
+		/// The expression in their version.
+		theirs: Option<Expression>,
+	},
+
+	/// Conflicting changes to the definition of a name.
+	Name {
+		/// The name.
+		name: String,
+		/// The definition in the common ancestor.
+		base: Option<Reference>,
+		/// The definition in our version.
+		ours: Option<Reference>,
+		/// The definition in their version.
+		theirs: Option<Reference>,
+	},
+
+	/// Conflicting changes to the metadata of a group. Metadata is merged 
+	/// field by field, so both `ours` and `theirs` include the fields which 
+	/// were merged without conflict, and differ only in the conflicting 
+	/// fields.
+	MetaData {
+		/// The group.
+		group: Reference,
+		/// The metadata in the common ancestor.
+		base: Option<MetaData>,
+		/// The merged metadata, keeping our version of conflicting fields.
+		ours: Option<MetaData>,
+		/// The merged metadata, taking their version of conflicting fields.
+		theirs: Option<MetaData>,
+	},
+}
+
+
+impl Conflict {
+	/// Returns a `Sequence` of operations which will resolve the conflict in 
+	/// favor of the given side, when applied after the merge's non-conflicting 
+	/// changes.
+	///
+	/// The non-conflicting changes keep our version of every conflicting 
+	/// value, so the `Sequence` returned for `Side::Ours` is empty, and 
+	/// applying it leaves the palette unchanged. It is still recorded in the 
+	/// operation history like any other operation.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::data::MetaData;
+	/// use palette::merge::{Merge, Side};
+	/// use palette::operation::*;
+	/// 
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// let line = Reference::line_of(&Address::new(0, 0, 0));
+	/// let base = pal.snapshot();
+	///
+	/// // Their version names the line and gives it 4 columns.
+	/// pal.apply(Box::new(SetMetaData::new(line.clone(), Some(MetaData {
+	/// 	name: Some("Greys".into()),
+	/// 	column_count: Some(4),
+	/// 	..Default::default()
+	/// })))).unwrap();
+	/// let theirs = pal.snapshot();
+	///
+	/// // Our version labels the line and gives it 2 columns.
+	/// pal.restore(&base);
+	/// pal.apply(Box::new(SetMetaData::new(line.clone(), Some(MetaData {
+	/// 	format_label: Some("Line".into()),
+	/// 	column_count: Some(2),
+	/// 	..Default::default()
+	/// })))).unwrap();
+	///
+	/// // Only the column count conflicts.
+	/// let merge = Merge::new(&base, &pal.snapshot(), &theirs);
+	/// assert_eq!(merge.conflicts.len(), 1);
+	/// pal.apply(Box::new(merge.to_operations())).unwrap();
+	/// assert_eq!(pal.snapshot().name(&line), Some("Greys"));
+	/// assert_eq!(pal.snapshot().column_count(&line), 2);
+	///
+	/// // Resolving in favor of our version changes nothing.
+	/// pal.apply(Box::new(merge.conflicts[0].resolve(Side::Ours))).unwrap();
+	/// assert_eq!(pal.snapshot().column_count(&line), 2);
+	///
+	/// pal.apply(Box::new(merge.conflicts[0].resolve(Side::Theirs))).unwrap();
+	/// assert_eq!(pal.snapshot().column_count(&line), 4);
+	/// assert_eq!(pal.snapshot().name(&line), Some("Greys"));
+	/// assert_eq!(pal.snapshot().metadata[&line].format_label, 
+	/// 	Some("Line".into()));
+	/// ```
+	pub fn resolve(&self, side: Side) -> Sequence {
+		let mut diff = Diff::default();
+		if side == Side::Theirs {
+			match *self {
+				Conflict::Cell {address, ref ours, ref theirs, ..} 
+					=> diff.cells.push(
+						cell_diff(address, ours.clone(), theirs.clone())),
+
+				Conflict::Name {ref name, ref ours, ref theirs, ..} 
+					=> diff.names.push(
+						name_diff(name.clone(), ours.clone(), theirs.clone())),
+
+				Conflict::MetaData {ref group, ref ours, ref theirs, ..} 
+					=> diff.metadata.push(MetaDataDiff {
+						group: group.clone(),
+						old: ours.clone(),
+						new: theirs.clone(),
+					}),
+			}
+		}
+		diff.to_operations()
+	}
+}
+
+
+impl fmt::Display for Conflict {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Conflict::Cell {address, ref base, ref ours, ref theirs}
+				=> write!(f, "! cell {}: ours {}, theirs {}, base {}",
+					address,
+					Version(ours),
+					Version(theirs),
+					Version(base)),
+
+			Conflict::Name {ref name, ref base, ref ours, ref theirs}
+				=> write!(f, "! name {}: ours {}, theirs {}, base {}",
+					name,
+					Version(ours),
+					Version(theirs),
+					Version(base)),
+
+			Conflict::MetaData {ref group, ref base, ref ours, ref theirs}
+				=> write!(f, "! meta {}: ours {}, theirs {}, base {}",
+					group,
+					Version(ours),
+					Version(theirs),
+					Version(base)),
+		}
+	}
+}
+
+
+/// Displays an optional version of a value, or `-` if it does not exist.
+struct Version<'a, T: 'a>(&'a Option<T>);
+
+
+impl<'a, T> fmt::Display for Version<'a, T> where T: fmt::Display {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self.0 {
+			Some(ref value) => write!(f, "{}", value),
+			None => write!(f, "-"),
+		}
+	}
+}
@@ -29,6 +29,7 @@
 // Local imports.
 use address::Address;
 use data::Data;
+use expression::Expression;
 use operation::{
 	set_target,
 	HistoryEntry,
@@ -50,20 +51,49 @@ use result::Result;
 ///
 /// ```rust
 /// use palette::*;
+/// use palette::operation::*;
+/// use palette::colorspace::ColorSpace;
 /// 
 /// let mut pal = Palette::new("Example", Format::Default, true);
 ///
 /// pal.apply(Box::new(InsertCell::new())).unwrap();
 ///
 /// assert_eq!(pal.color(Address::new(0, 0, 0)), None);
-/// 
+///
+/// // A cell without a color is free, so the next insertion fills it.
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+/// )).unwrap();
+/// assert_eq!(pal.len(), 1);
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(12, 50, 78)));
+///
+/// // Expressions which would depend on themselves are rejected.
+/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(b)
+/// 	.with_expression(Expression::Color(Color::new(10, 20, 30)))
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(a)
+/// 	.overwrite(true)
+/// 	.with_expression(
+/// 		Expression::Blend(b.into(), b.into(), 0.5, ColorSpace::Rgb))
+/// )).unwrap();
+///
+/// assert!(pal.apply(Box::new(InsertCell::new()
+/// 	.located_at(b)
+/// 	.overwrite(true)
+/// 	.with_expression(Expression::Reference(a.into()))
+/// )).is_err());
 /// ```
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct InsertCell {
 	/// The location to start placing the colors.
 	location: Option<Address>,
 	/// Whether to overwrite existing cells when generating new ones.
 	overwrite: bool,
+	/// The expression to place in the new cell.
+	expression: Expression,
 }
 
 
@@ -74,6 +104,7 @@ impl InsertCell {
 		InsertCell {
 			location: None,
 			overwrite: false,
+			expression: Expression::Empty,
 		}
 	}
 
@@ -88,6 +119,12 @@ impl InsertCell {
 		self.overwrite = overwrite;
 		self
 	}
+
+	/// Sets the expression to place in the cell.
+	pub fn with_expression(mut self, expression: Expression) -> InsertCell {
+		self.expression = expression;
+		self
+	}
 }
 
 
@@ -118,7 +155,7 @@ impl PaletteOperation for InsertCell {
 
 		// Set target.
 		let mut undo = Undo::new_for(self);
-		set_target(data, target, Default::default(), &mut undo)?;
+		set_target(data, target, self.expression.clone(), &mut undo)?;
 		
 		Ok(HistoryEntry {
 			info: self.info(),
@@ -138,10 +175,13 @@ impl PaletteOperation for InsertCell {
 ///
 /// ```rust
 /// use palette::*;
+/// use palette::operation::*;
 /// 
 /// let mut pal = Palette::new("Example", Format::Default, true);
 ///
-/// pal.apply(Box::new(InsertCell::new(Color::new(12, 50, 78)))).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+/// )).unwrap();
 /// pal.apply(Box::new(DeleteCell::new(Address::new(0, 0, 0)))).unwrap();
 /// 
 /// assert_eq!(pal.len(), 0);
@@ -49,18 +49,36 @@ use std::mem;
 ///
 /// ```rust
 /// use palette::*;
+/// use palette::operation::*;
 ///
 /// let mut pal = Palette::new("Example", Format::Default, true);
 ///
 /// pal.apply(Box::new(
 /// 	Sequence::new(vec![
-///			Box::new(InsertColor::new(Color::new(10, 10, 10))),
-///			Box::new(InsertColor::new(Color::new(20, 20, 20)))
+///			Box::new(InsertCell::new()
+///				.with_expression(Expression::Color(Color::new(10, 10, 10)))),
+///			Box::new(InsertCell::new()
+///				.with_expression(Expression::Color(Color::new(20, 20, 20))))
 ///		])
 /// )).unwrap();
 ///
 /// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(10, 10, 10)));
 /// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(20, 20, 20)));
+///
+/// // Undoing a sequence reverts its operations in reverse order.
+/// pal.apply(Box::new(
+/// 	Sequence::new(vec![
+///			Box::new(InsertCell::new()
+///				.located_at(Address::new(0, 0, 2))),
+///			Box::new(InsertCell::new()
+///				.located_at(Address::new(0, 0, 2))
+///				.overwrite(true)
+///				.with_expression(Expression::Color(Color::new(30, 30, 30))))
+///		])
+/// )).unwrap();
+/// pal.undo().unwrap();
+///
+/// assert_eq!(pal.len(), 2);
 /// ```
 #[derive(Debug)]
 pub struct Sequence {
@@ -94,6 +112,8 @@ impl PaletteOperation for Sequence {
 			undo_sequence.push(entry.undo);
 		}
 
+		// Undo operations must be applied in the reverse order.
+		undo_sequence.reverse();
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(Sequence::new(undo_sequence)),
@@ -111,12 +131,13 @@ impl PaletteOperation for Sequence {
 ///
 /// ```rust
 /// use palette::*;
+/// use palette::operation::*;
 ///
 /// let mut pal = Palette::new("Example", Format::Default, true);
 ///
 /// pal.apply(Box::new(
-/// 	Repeat::new(Box::new(
-///			InsertColor::new(Color::new(50, 50, 78))
+/// 	Repeat::new(Box::new(InsertCell::new()
+///			.with_expression(Expression::Color(Color::new(50, 50, 78)))
 ///		)).repeat(3)
 /// )).unwrap();
 ///
@@ -166,6 +187,8 @@ impl PaletteOperation for Repeat {
 			undo_sequence.push(entry.undo);
 		}
 
+		// Undo operations must be applied in the reverse order.
+		undo_sequence.reverse();
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(Sequence::new(undo_sequence)),
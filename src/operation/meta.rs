@@ -0,0 +1,303 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines operations for modifying the palette's metadata.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference, LINE_MAX, COLUMN_MAX};
+use data::{line_group, page_group, Data, MetaData};
+use operation::{
+	set_target,
+	HistoryEntry,
+	OperationInfo,
+	PaletteOperation,
+	Sequence,
+	Undo,
+};
+use result::{Error, Result};
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SetMetaData
+////////////////////////////////////////////////////////////////////////////////
+/// Replaces the metadata of a group, or removes it if no metadata is given.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::MetaData;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// let line = Reference::line_of(&Address::new(0, 0, 0));
+///
+/// pal.apply(Box::new(SetMetaData::new(line.clone(), Some(MetaData {
+/// 	column_count: Some(2),
+/// 	..Default::default()
+/// })))).unwrap();
+///
+/// pal.apply(Box::new(Repeat::new(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+/// )).repeat(3))).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 1, 0)), Some(Color::new(12, 50, 78)));
+///
+/// pal.undo().unwrap();
+/// pal.undo().unwrap();
+/// assert_eq!(pal.snapshot().column_count(&line), 255);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetMetaData {
+	/// The group whose metadata to set.
+	group: Reference,
+	/// The new metadata of the group.
+	metadata: Option<MetaData>,
+}
+
+
+impl SetMetaData {
+	/// Creates a new SetMetaData operation.
+	#[inline]
+	pub fn new(group: Reference, metadata: Option<MetaData>) -> SetMetaData {
+		SetMetaData {
+			group: group,
+			metadata: metadata,
+		}
+	}
+}
+
+
+impl PaletteOperation for SetMetaData {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Set Metadata",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let old = data.set_metadata(self.group.clone(), self.metadata.clone())?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(SetMetaData::new(self.group.clone(), old)),
+		})
+	}
+}
+
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ResizeGroup
+////////////////////////////////////////////////////////////////////////////////
+/// Sets the number of lines in a page group, or the number of columns in a 
+/// line group. The operation fails if any cells would no longer lie within the
+/// group, unless it is configured to move them. Moved cells are placed in the 
+/// first free addresses of the resized group, or of the groups following it.
+/// Expressions which reference moved cells are not updated.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// let line = Reference::line_of(&Address::new(0, 0, 0));
+/// for i in 0..4 {
+/// 	pal.apply(Box::new(InsertCell::new()
+/// 		.with_expression(Expression::Color(Color::new(i, i, i)))
+/// 	)).unwrap();
+/// }
+///
+/// // Shrinking the line would leave two cells outside of it.
+/// assert!(pal.apply(Box::new(ResizeGroup::new(line.clone(), 2))).is_err());
+///
+/// pal.apply(Box::new(ResizeGroup::new(line.clone(), 2).move_cells(true)))
+/// 	.unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 3)), None);
+/// assert_eq!(pal.color(Address::new(0, 1, 0)), Some(Color::new(2, 2, 2)));
+/// assert_eq!(pal.color(Address::new(0, 1, 1)), Some(Color::new(3, 3, 3)));
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 3)), Some(Color::new(3, 3, 3)));
+/// assert_eq!(pal.color(Address::new(0, 1, 0)), None);
+/// assert_eq!(pal.snapshot().column_count(&line), 255);
+///
+/// pal.redo().unwrap();
+/// assert_eq!(pal.snapshot().column_count(&line), 2);
+/// ```
+///
+/// If the moved cells cannot be placed, the palette is left unchanged:
+///
+/// ```rust
+/// use palette::*;
+/// use palette::layout::GridLayout;
+/// use palette::operation::*;
+/// use palette::sync::Shared;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.set_layout(Shared::new(GridLayout::new(1, 1, 4)));
+/// let line = Reference::line_of(&Address::new(0, 0, 0));
+/// for i in 0..4 {
+/// 	pal.apply(Box::new(InsertCell::new()
+/// 		.with_expression(Expression::Color(Color::new(i, i, i)))
+/// 	)).unwrap();
+/// }
+///
+/// assert!(pal.apply(Box::new(ResizeGroup::new(line.clone(), 2)
+/// 	.move_cells(true))).is_err());
+/// assert_eq!(pal.len(), 4);
+/// assert_eq!(pal.color(Address::new(0, 0, 3)), Some(Color::new(3, 3, 3)));
+/// assert_eq!(pal.snapshot().column_count(&line), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResizeGroup {
+	/// The page or line group to resize.
+	group: Reference,
+	/// The new line count of a page group, or column count of a line group.
+	size: u8,
+	/// Whether to move cells which would no longer lie within the group.
+	move_cells: bool,
+}
+
+
+impl ResizeGroup {
+	/// Creates a new ResizeGroup operation.
+	#[inline]
+	pub fn new(group: Reference, size: u8) -> ResizeGroup {
+		ResizeGroup {
+			group: group,
+			size: size,
+			move_cells: false,
+		}
+	}
+
+	/// Configures the operation to move cells which would no longer lie 
+	/// within the group, instead of failing.
+	pub fn move_cells(mut self, move_cells: bool) -> ResizeGroup {
+		self.move_cells = move_cells;
+		self
+	}
+}
+
+
+impl PaletteOperation for ResizeGroup {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Resize Group",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		// Get the first address of the group and the range of addresses 
+		// removed from it.
+		let (lines, first, start, end) = if let Some(page) = 
+			page_group(&self.group) 
+		{
+			(true,
+				Address::new(page, 0, 0),
+				Address::new(page, self.size, 0),
+				Address::new(page, LINE_MAX, COLUMN_MAX))
+		} else if let Some((page, line)) = line_group(&self.group) {
+			(false,
+				Address::new(page, line, 0),
+				Address::new(page, line, self.size),
+				Address::new(page, line, COLUMN_MAX))
+		} else {
+			return Err(Error::InvalidGroup(self.group.clone()));
+		};
+		if self.size == 0 {
+			return Err(Error::EmptyGroup(self.group.clone()));
+		}
+
+		let old = data.metadata.get(&self.group).cloned();
+		let mut undo = Undo::new_for(self);
+		let result = self.resize(data, lines, first, (start, end), &mut undo);
+
+		// The group must be restored before the moved cells.
+		let mut undo = Sequence::new(vec![
+			Box::new(SetMetaData::new(self.group.clone(), old)),
+			Box::new(undo),
+		]);
+
+		match result {
+			Ok(()) => Ok(HistoryEntry {
+				info: self.info(),
+				undo: Box::new(undo),
+			}),
+			Err(error) => {
+				// Restore any cells already moved before reporting the error.
+				undo.apply(data)?;
+				Err(error)
+			},
+		}
+	}
+}
+
+
+impl ResizeGroup {
+	/// Resizes the group, moving the cells between the given addresses to the
+	/// first free addresses after `first` if the operation is configured to 
+	/// move cells. Every change is recorded in the given `Undo`.
+	fn resize(
+		&self, 
+		data: &mut Data, 
+		lines: bool, 
+		first: Address, 
+		removed: (Address, Address),
+		undo: &mut Undo)
+		-> Result<()>
+	{
+		// Remove the cells to be moved.
+		let mut moved = Vec::new();
+		if self.move_cells {
+			for address in data.stranded_cells(removed.0, removed.1) {
+				let expression = data.remove_cell(address)?;
+				undo.record(address, Some(expression.clone()));
+				moved.push(expression);
+			}
+		}
+
+		if lines {
+			data.set_line_count(self.group.clone(), self.size)?;
+		} else {
+			data.set_column_count(self.group.clone(), self.size)?;
+		}
+
+		// Place the moved cells.
+		if !moved.is_empty() {
+			let targets = data.find_targets(moved.len(), first, false, None)?;
+			for (target, expression) in targets.into_iter().zip(moved) {
+				set_target(data, target, expression, undo)?;
+			}
+		}
+		Ok(())
+	}
+}
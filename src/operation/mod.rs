@@ -34,6 +34,10 @@ mod basic;
 #[warn(missing_docs)]
 mod combine;
 #[warn(missing_docs)]
+mod meta;
+#[warn(missing_docs)]
+mod name;
+#[warn(missing_docs)]
 mod undo;
 
 // Submodule re-exports.
@@ -45,6 +49,15 @@ pub use self::combine::{
 	Repeat,
 	Sequence,
 };
+pub use self::meta::{
+	ResizeGroup,
+	SetMetaData,
+};
+pub use self::name::{
+	DefineName,
+	DeleteName,
+	RenameName,
+};
 pub use self::undo::Undo;
 
 // Local imports.
@@ -53,11 +66,10 @@ use cell::Cell;
 use data::Data;
 use expression::Expression;
 use result::{Error, Result};
+use sync::{Shared, Threadsafe, WeakShared};
 
 // Standard imports.
 use std::fmt;
-use std::rc::{Rc, Weak};
-use std::mem;
 
 
 /// Returns a weak reference to the source element located at the given address 
@@ -69,12 +81,12 @@ pub(crate) fn source(
 	address: Address, 
 	make_sources: bool,
 	undo: &mut Undo) 
-	-> Result<Weak<Cell>>
+	-> Result<WeakShared<Cell>>
 {
 	if let Some(cell) = data.cell(address) {
-		Ok(Rc::downgrade(&cell))
+		Ok(Shared::downgrade(&cell))
 	} else if make_sources {
-		let cell = Rc::downgrade(&data.create_cell(address)?);
+		let cell = Shared::downgrade(&data.create_cell(address)?);
 		undo.record(address, None);
 		Ok(cell)
 	} else {
@@ -88,7 +100,7 @@ pub(crate) fn target(
 	data: &mut Data, 
 	address: Address, 
 	undo: &mut Undo)
-	-> Result<Rc<Cell>>
+	-> Result<Shared<Cell>>
 {
 	if let Some(cell) = data.cell(address) {
 		Ok(cell)
@@ -108,11 +120,12 @@ pub(crate) fn set_target(
 	undo: &mut Undo)
 	-> Result<()>
 {
-	// Get the target cell.
-	let target = target(data, address, undo)?;
+	// Check the new element before creating the target cell.
+	data.check_expression(address, &new_element)?;
+	target(data, address, undo)?;
 
 	// Insert new element into palette.
-	let cur = mem::replace(&mut *target.borrow_mut(), new_element);
+	let cur = data.set_expression(address, new_element)?;
 	undo.record(address, Some(cur));
 	Ok(())
 }
@@ -123,7 +136,7 @@ pub(crate) fn set_target(
 // PaletteOperation
 ////////////////////////////////////////////////////////////////////////////////
 /// Provides the methods for modifying palettes.
-pub trait PaletteOperation: fmt::Debug {
+pub trait PaletteOperation: fmt::Debug + Threadsafe {
 	/// Returns information about the operation.
 	fn info(&self) -> OperationInfo;
 
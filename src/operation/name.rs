@@ -0,0 +1,240 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines operations for managing the palette's named references.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Reference;
+use data::Data;
+use operation::{
+	HistoryEntry,
+	OperationInfo,
+	PaletteOperation,
+};
+use result::Result;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DefineName
+////////////////////////////////////////////////////////////////////////////////
+/// Defines a name for a `Reference`, replacing any previous definition.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression("@accent".parse().unwrap())
+/// )).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), None);
+///
+/// pal.apply(Box::new(
+/// 	DefineName::new("accent", Address::new(0, 0, 0).into())
+/// )).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(12, 50, 78)));
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DefineName {
+	/// The name to define.
+	name: String,
+	/// The `Reference` to assign to the name.
+	reference: Reference,
+}
+
+
+impl DefineName {
+	/// Creates a new DefineName operation.
+	#[inline]
+	pub fn new<S>(name: S, reference: Reference) -> DefineName 
+		where S: Into<String>
+	{
+		DefineName {
+			name: name.into(),
+			reference: reference,
+		}
+	}
+}
+
+
+impl PaletteOperation for DefineName {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Define Name",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let old = data.define_name(self.name.clone(), self.reference.clone())?;
+
+		let undo: Box<PaletteOperation> = match old {
+			Some(old) => Box::new(DefineName::new(self.name.clone(), old)),
+			None => Box::new(DeleteName::new(self.name.clone())),
+		};
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: undo,
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DeleteName
+////////////////////////////////////////////////////////////////////////////////
+/// Removes the definition of a name.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(DefineName::new("skin", Reference::all()))).unwrap();
+///
+/// assert!(pal.apply(Box::new(DeleteName::new("skin"))).is_ok());
+/// assert!(pal.apply(Box::new(DeleteName::new("skin"))).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeleteName {
+	/// The name to remove.
+	name: String,
+}
+
+
+impl DeleteName {
+	/// Creates a new DeleteName operation.
+	#[inline]
+	pub fn new<S>(name: S) -> DeleteName where S: Into<String> {
+		DeleteName {name: name.into()}
+	}
+}
+
+
+impl PaletteOperation for DeleteName {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Delete Name",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let old = data.remove_name(&self.name)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(DefineName::new(self.name.clone(), old)),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RenameName
+////////////////////////////////////////////////////////////////////////////////
+/// Renames a name, updating every expression and name definition which uses 
+/// it.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+/// )).unwrap();
+/// pal.apply(Box::new(
+/// 	DefineName::new("accent", Address::new(0, 0, 0).into())
+/// )).unwrap();
+/// pal.apply(Box::new(InsertCell::new()
+/// 	.with_expression("@accent".parse().unwrap())
+/// )).unwrap();
+///
+/// pal.apply(Box::new(RenameName::new("accent", "ui.accent"))).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(12, 50, 78)));
+///
+/// pal.undo().unwrap();
+/// pal.redo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(12, 50, 78)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenameName {
+	/// The name to rename.
+	old: String,
+	/// The new name.
+	new: String,
+}
+
+
+impl RenameName {
+	/// Creates a new RenameName operation.
+	#[inline]
+	pub fn new<S, T>(old: S, new: T) -> RenameName 
+		where 
+			S: Into<String>,
+			T: Into<String>,
+	{
+		RenameName {
+			old: old.into(),
+			new: new.into(),
+		}
+	}
+}
+
+
+impl PaletteOperation for RenameName {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Rename Name",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		data.rename_name(&self.old, &self.new)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(RenameName::new(self.new.clone(), self.old.clone())),
+		})
+	}
+}
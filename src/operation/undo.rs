@@ -39,7 +39,6 @@ use result::Result;
 
 // Standard imports.
 use std::collections::HashMap;
-use std::mem;
 
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -53,6 +52,46 @@ use std::mem;
 /// "address: None" entry in the `Undo`,  nothing will overwrite it. This
 /// ensures  that the element at that address will be deleted if the `Undo`
 /// operation is applied later.
+///
+/// Applying an `Undo` makes its changes to a copy of the palette, which only
+/// replaces the palette once every saved expression has been restored. An 
+/// `Undo` that fails leaves the palette untouched, and may be applied again.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::Data;
+/// use palette::operation::*;
+///
+/// let mut dat: Data = Default::default();
+/// Format::Zpl.initialize(&mut dat);
+/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 14, 0));
+/// dat.create_cell(a).unwrap();
+///
+/// let mut undo = Undo::new_for(&InsertCell::new());
+/// undo.record(a, None);
+/// undo.record(b, Some(Expression::Color(Color::new(10, 20, 30))));
+///
+/// assert!(undo.apply(&mut dat).is_err());
+/// assert!(dat.cell(a).is_some());
+///
+/// // Restoring expressions which reference each other also fails.
+/// let (c, d) = (Address::new(0, 0, 1), Address::new(0, 0, 2));
+/// let black = Expression::Color(Color::new(0, 0, 0));
+/// for &address in &[c, d] {
+/// 	dat.create_cell(address).unwrap();
+/// 	dat.set_expression(address, black.clone()).unwrap();
+/// }
+///
+/// let mut undo = Undo::new_for(&InsertCell::new());
+/// undo.record(c, Some(Expression::Reference(d.into())));
+/// undo.record(d, Some(Expression::Reference(c.into())));
+///
+/// assert!(undo.apply(&mut dat).is_err());
+/// assert_eq!(dat.color(c), Some(Color::new(0, 0, 0)));
+/// assert_eq!(dat.color(d), Some(Color::new(0, 0, 0)));
+/// ```
 #[derive(Debug)]
 pub struct Undo {
 	/// The operation being undone.
@@ -109,37 +148,45 @@ impl PaletteOperation for Undo {
 	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
 		let mut redo = Undo::new();
 
-		let saved = mem::replace(&mut self.saved, HashMap::new());
+		// The changes are made to a copy of the palette, which only replaces
+		// it once every saved expression has been restored.
+		let mut restored = data.share();
 
-		for (address, item) in saved {
-			match (item.is_some(), data.cell(address).is_some()) {
+		// All affected cells are cleared before any saved expressions are 
+		// restored. This ensures that no intermediate state contains a cycle.
+		let mut restore = Vec::new();
+		for (&address, item) in &self.saved {
+			match (item.clone(), restored.cell(address).is_some()) {
 
-				(true, true) => { // The cell was modified.
-					let elem = item.unwrap();
-					let cell = data.cell(address).unwrap();
-					let cur = mem::replace(&mut *cell.borrow_mut(), elem);
+				(Some(elem), true) => { // The cell was modified.
+					let cur = restored
+						.set_expression(address, Expression::Empty)?;
 					redo.record(address, Some(cur));
-					continue;
+					restore.push((address, elem));
 				},
 
-				(true, false) => { // The cell was deleted.
-					let elem = item.unwrap();
-					let cell = data.create_cell(address).unwrap();
-					mem::replace(&mut *cell.borrow_mut(), elem);
+				(Some(elem), false) => { // The cell was deleted.
+					restored.create_cell(address)?;
 					redo.record(address, None);
-					continue;
+					restore.push((address, elem));
 				},
 
-				(false, true) => { // The cell was added.
-					let cur = data.remove_cell(address)?;
+				(None, true) => { // The cell was added.
+					let cur = restored.remove_cell(address)?;
 					redo.record(address, Some(cur));
-					continue;
 				},
 
 				_ => panic!("null entry in Undo operation")
 			}
 		}
 
+		for (address, elem) in restore {
+			restored.set_expression(address, elem)?;
+		}
+
+		*data = restored;
+		self.saved.clear();
+
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(redo),
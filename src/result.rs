@@ -71,18 +71,77 @@ pub enum Error {
 	
 	/// An element could not be created because the address was occupied.
 	AddressInUse(Address),
+
+	/// An expression could not be stored because the cell at the address would
+	/// depend upon itself.
+	CyclicReference(Address),
+
+	/// A `Reference` refers to a name which is not defined.
+	UndefinedName(String),
+
+	/// A `Reference` refers to a name which is defined in terms of itself.
+	CyclicName(String),
+
+	/// Text could not be parsed as an `Address` or `Reference`.
+	MalformedReference(String),
+
+	/// A name was provided which is not a valid `Reference` name.
+	InvalidName(String),
+
+	/// A name could not be defined because it is already in use.
+	NameInUse(String),
+
+	/// A flat color index was provided that lies beyond the last address in 
+	/// the palette.
+	InvalidIndex(usize),
+
+	/// A `Reference` was provided which is not a page or line group of the 
+	/// required kind.
+	InvalidGroup(Reference),
+
+	/// A page or line group was given no lines or columns.
+	EmptyGroup(Reference),
+
+	/// A group could not be resized because the cell at the address would no 
+	/// longer lie within the palette's layout.
+	StrandedCell(Address),
 }
 
 
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
 		match *self {
-			Error::EmptyAddress(address)
+			Error::EmptyAddress(address) |
+			Error::CyclicReference(address) |
+			Error::StrandedCell(address)
 				=> write!(f, "{}: {}", 
 					error::Error::description(self), 
 					address
 				),
 
+			Error::UndefinedName(ref name) |
+			Error::CyclicName(ref name) |
+			Error::MalformedReference(ref name) |
+			Error::InvalidName(ref name) |
+			Error::NameInUse(ref name)
+				=> write!(f, "{}: {}", 
+					error::Error::description(self), 
+					name
+				),
+
+			Error::InvalidIndex(index)
+				=> write!(f, "{}: {}", 
+					error::Error::description(self), 
+					index
+				),
+
+			Error::InvalidGroup(ref group) |
+			Error::EmptyGroup(ref group)
+				=> write!(f, "{}: {}", 
+					error::Error::description(self), 
+					group
+				),
+
 			_	=> write!(f, "{}", error::Error::description(self))
 		}
 	}
@@ -116,6 +175,36 @@ impl error::Error for Error {
 
 			Error::AddressInUse(..)
 				=> "the address is in use",
+
+			Error::CyclicReference(..)
+				=> "expression would create a cyclic reference",
+
+			Error::UndefinedName(..)
+				=> "reference to an undefined name",
+
+			Error::CyclicName(..)
+				=> "name is defined in terms of itself",
+
+			Error::MalformedReference(..)
+				=> "malformed address or reference",
+
+			Error::InvalidName(..)
+				=> "invalid name",
+
+			Error::NameInUse(..)
+				=> "the name is in use",
+
+			Error::InvalidIndex(..)
+				=> "index lies outside of allowed range",
+
+			Error::InvalidGroup(..)
+				=> "reference is not a group of the required kind",
+
+			Error::EmptyGroup(..)
+				=> "group must contain at least one line or column",
+
+			Error::StrandedCell(..)
+				=> "cell would lie outside of the resized group",
 		}
 	}
 }
@@ -0,0 +1,629 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides the storage backends for a palette's cells.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, LINE_MAX};
+use cell::Cell;
+use data::Data;
+use layout::Layout;
+use sync::Shared;
+
+// Standard imports.
+use std::collections::{btree_map, BTreeMap, BTreeSet, Bound};
+use std::fmt;
+use std::mem;
+use std::ops::RangeBounds;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CellStorage
+////////////////////////////////////////////////////////////////////////////////
+/// A map assigning addresses to `Palette` cells.
+///
+/// Sparse storage keeps the cells in a `BTreeMap`, and is best suited to 
+/// palettes whose cells are scattered over a large layout. Dense storage keeps
+/// the cells in arrays indexed by page, line, and column, which is faster and 
+/// smaller for palettes which fill most of a small layout, such as ZPL 
+/// palettes. Both provide the same interface and iterate in address order.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::Data;
+/// 
+/// let mut dat: Data = Default::default();
+/// assert!(!dat.cells.is_dense());
+///
+/// dat.create_cell(Address::new(0, 1, 2)).unwrap();
+/// dat.create_cell(Address::new(0, 0, 5)).unwrap();
+/// dat.cells.make_dense();
+/// assert!(dat.cells.is_dense());
+///
+/// let addresses: Vec<_> = dat.cells.keys().collect();
+/// assert_eq!(addresses, vec![Address::new(0, 0, 5), Address::new(0, 1, 2)]);
+/// ```
+#[derive(Clone)]
+pub enum CellStorage {
+	/// Stores cells in a `BTreeMap`.
+	Sparse(BTreeMap<Address, Shared<Cell>>),
+	/// Stores cells in arrays indexed by page, line, and column.
+	Dense(DenseStorage),
+}
+
+
+impl CellStorage {
+	/// Creates a new, empty sparse storage.
+	pub fn sparse() -> CellStorage {
+		CellStorage::Sparse(BTreeMap::new())
+	}
+
+	/// Creates a new, empty dense storage.
+	pub fn dense() -> CellStorage {
+		CellStorage::Dense(Default::default())
+	}
+
+	/// Returns whether the cells are stored densely.
+	pub fn is_dense(&self) -> bool {
+		match *self {
+			CellStorage::Sparse(_) => false,
+			CellStorage::Dense(_) => true,
+		}
+	}
+
+	/// Converts the storage to dense storage, keeping its cells.
+	pub fn make_dense(&mut self) {
+		if !self.is_dense() {
+			let mut dense = CellStorage::dense();
+			for (address, cell) in self.iter() {
+				dense.insert(address, cell.clone());
+			}
+			*self = dense;
+		}
+	}
+
+	/// Converts the storage to sparse storage, keeping its cells.
+	pub fn make_sparse(&mut self) {
+		if self.is_dense() {
+			let mut sparse = CellStorage::sparse();
+			for (address, cell) in self.iter() {
+				sparse.insert(address, cell.clone());
+			}
+			*self = sparse;
+		}
+	}
+
+	/// Returns a copy of the storage containing copies of its cells, so that 
+	/// the cells of the copy can be modified without affecting the original.
+	pub fn deep_clone(&self) -> CellStorage {
+		let mut storage = match *self {
+			CellStorage::Sparse(_) => CellStorage::sparse(),
+			CellStorage::Dense(_) => CellStorage::dense(),
+		};
+		for (address, cell) in self.iter() {
+			storage.insert(address, Shared::new((**cell).clone()));
+		}
+		storage
+	}
+
+	/// Returns the number of stored cells.
+	pub fn len(&self) -> usize {
+		match *self {
+			CellStorage::Sparse(ref map) => map.len(),
+			CellStorage::Dense(ref dense) => dense.len,
+		}
+	}
+
+	/// Returns whether there are no stored cells.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the cell at the given address, if there is one.
+	pub fn get(&self, address: &Address) -> Option<&Shared<Cell>> {
+		match *self {
+			CellStorage::Sparse(ref map) => map.get(address),
+			CellStorage::Dense(ref dense) => dense.get(address),
+		}
+	}
+
+	/// Returns whether there is a cell at the given address.
+	pub fn contains_key(&self, address: &Address) -> bool {
+		self.get(address).is_some()
+	}
+
+	/// Stores a cell at the given address, returning the cell it replaces.
+	pub fn insert(&mut self, address: Address, cell: Shared<Cell>) 
+		-> Option<Shared<Cell>>
+	{
+		match *self {
+			CellStorage::Sparse(ref mut map) => map.insert(address, cell),
+			CellStorage::Dense(ref mut dense) => dense.insert(address, cell),
+		}
+	}
+
+	/// Removes and returns the cell at the given address.
+	pub fn remove(&mut self, address: &Address) -> Option<Shared<Cell>> {
+		match *self {
+			CellStorage::Sparse(ref mut map) => map.remove(address),
+			CellStorage::Dense(ref mut dense) => dense.remove(address),
+		}
+	}
+
+	/// Returns an iterator over the addresses and cells, in address order.
+	pub fn iter(&self) -> Iter {
+		self.range::<(Bound<Address>, Bound<Address>)>(
+			(Bound::Unbounded, Bound::Unbounded))
+	}
+
+	/// Returns an iterator over the addresses of the cells, in order.
+	pub fn keys(&self) -> Keys {
+		Keys {inner: self.iter()}
+	}
+
+	/// Returns an iterator over the cells, in address order.
+	pub fn values(&self) -> Values {
+		Values {inner: self.iter()}
+	}
+
+	/// Returns an iterator over the addresses and cells within the given 
+	/// range, in address order.
+	pub fn range<R>(&self, range: R) -> Iter where R: RangeBounds<Address> {
+		let start = owned_bound(range.start_bound());
+		let end = owned_bound(range.end_bound());
+		let inner = match *self {
+			CellStorage::Sparse(ref map) 
+				=> IterInner::Sparse(map.range((start, end))),
+
+			CellStorage::Dense(ref dense) => IterInner::Dense(DenseIter {
+				storage: dense,
+				next: match start {
+					Bound::Included(address) => Some(address),
+					Bound::Excluded(address) => address.successor(),
+					Bound::Unbounded => Some(Address::default()),
+				},
+				end: end,
+			}),
+		};
+		Iter {inner: inner}
+	}
+}
+
+
+/// Returns an owned copy of the given bound.
+fn owned_bound(bound: Bound<&Address>) -> Bound<Address> {
+	match bound {
+		Bound::Included(&address) => Bound::Included(address),
+		Bound::Excluded(&address) => Bound::Excluded(address),
+		Bound::Unbounded => Bound::Unbounded,
+	}
+}
+
+
+impl Default for CellStorage {
+	fn default() -> Self {
+		CellStorage::sparse()
+	}
+}
+
+
+impl fmt::Debug for CellStorage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+
+impl<'a> IntoIterator for &'a CellStorage {
+	type Item = (Address, &'a Shared<Cell>);
+	type IntoIter = Iter<'a>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DenseStorage
+////////////////////////////////////////////////////////////////////////////////
+/// Cell storage using arrays indexed by page, line, and column. The arrays 
+/// grow as cells are inserted, so that storage is only allocated for the 
+/// pages and lines in use.
+#[derive(Clone, Default)]
+pub struct DenseStorage {
+	/// The cells of each line of each page.
+	pages: Vec<Vec<Vec<Option<Shared<Cell>>>>>,
+	/// The number of stored cells.
+	len: usize,
+}
+
+
+impl DenseStorage {
+	/// Returns the slot for the given address, if it has been allocated.
+	fn slot(&self, address: &Address) -> Option<&Option<Shared<Cell>>> {
+		self.pages
+			.get(address.page as usize)
+			.and_then(|page| page.get(address.line as usize))
+			.and_then(|line| line.get(address.column as usize))
+	}
+
+	/// Returns the cell at the given address, if there is one.
+	fn get(&self, address: &Address) -> Option<&Shared<Cell>> {
+		self.slot(address).and_then(|slot| slot.as_ref())
+	}
+
+	/// Stores a cell at the given address, returning the cell it replaces.
+	fn insert(&mut self, address: Address, cell: Shared<Cell>) 
+		-> Option<Shared<Cell>>
+	{
+		let (page, line, column) = (
+			address.page as usize, 
+			address.line as usize, 
+			address.column as usize);
+
+		if self.pages.len() <= page {
+			self.pages.resize(page + 1, Vec::new());
+		}
+		let lines = &mut self.pages[page];
+		if lines.len() <= line {
+			lines.resize(line + 1, Vec::new());
+		}
+		let columns = &mut lines[line];
+		if columns.len() <= column {
+			columns.resize(column + 1, None);
+		}
+
+		let old = columns[column].take();
+		columns[column] = Some(cell);
+		if old.is_none() {self.len += 1;}
+		old
+	}
+
+	/// Removes and returns the cell at the given address.
+	fn remove(&mut self, address: &Address) -> Option<Shared<Cell>> {
+		let old = self.pages
+			.get_mut(address.page as usize)
+			.and_then(|page| page.get_mut(address.line as usize))
+			.and_then(|line| line.get_mut(address.column as usize))
+			.and_then(|slot| slot.take());
+		if old.is_some() {self.len -= 1;}
+		old
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Iter
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the addresses and cells of a `CellStorage`.
+#[derive(Debug)]
+pub struct Iter<'a> {
+	/// The iterator for the storage backend.
+	inner: IterInner<'a>,
+}
+
+
+impl<'a> Iterator for Iter<'a> {
+	type Item = (Address, &'a Shared<Cell>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.inner {
+			IterInner::Sparse(ref mut range) 
+				=> range.next().map(|(&address, cell)| (address, cell)),
+			IterInner::Dense(ref mut iter) => iter.next(),
+		}
+	}
+}
+
+
+/// The iterators for each storage backend.
+#[derive(Debug)]
+enum IterInner<'a> {
+	/// Iterates over sparse storage.
+	Sparse(btree_map::Range<'a, Address, Shared<Cell>>),
+	/// Iterates over dense storage.
+	Dense(DenseIter<'a>),
+}
+
+
+/// An iterator over the addresses and cells of a `DenseStorage`.
+struct DenseIter<'a> {
+	/// The storage being iterated over.
+	storage: &'a DenseStorage,
+	/// The next candidate address.
+	next: Option<Address>,
+	/// The end of the iterated range.
+	end: Bound<Address>,
+}
+
+
+impl<'a> DenseIter<'a> {
+	/// Returns whether the given address lies past the end of the range.
+	fn is_past_end(&self, address: Address) -> bool {
+		match self.end {
+			Bound::Included(end) => address > end,
+			Bound::Excluded(end) => address >= end,
+			Bound::Unbounded => false,
+		}
+	}
+}
+
+
+impl<'a> Iterator for DenseIter<'a> {
+	type Item = (Address, &'a Shared<Cell>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let pages = &self.storage.pages;
+		while let Some(address) = self.next {
+			if self.is_past_end(address) || 
+				address.page as usize >= pages.len() 
+			{
+				self.next = None;
+				break;
+			}
+
+			let lines = &pages[address.page as usize];
+			if address.line as usize >= lines.len() {
+				// Skip to the next page.
+				self.next = address.page
+					.checked_add(1)
+					.map(|page| Address::new(page, 0, 0));
+				continue;
+			}
+
+			let columns = &lines[address.line as usize];
+			if address.column as usize >= columns.len() {
+				// Skip to the next line.
+				self.next = if address.line < LINE_MAX {
+					Some(Address::new(address.page, address.line + 1, 0))
+				} else {
+					address.page
+						.checked_add(1)
+						.map(|page| Address::new(page, 0, 0))
+				};
+				continue;
+			}
+
+			self.next = address.successor();
+			if let Some(ref cell) = columns[address.column as usize] {
+				return Some((address, cell));
+			}
+		}
+		None
+	}
+}
+
+
+impl<'a> fmt::Debug for DenseIter<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DenseIter")
+			.field("next", &self.next)
+			.field("end", &self.end)
+			.finish()
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Keys
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the addresses of the cells in a `CellStorage`.
+#[derive(Debug)]
+pub struct Keys<'a> {
+	/// The underlying iterator.
+	inner: Iter<'a>,
+}
+
+
+impl<'a> Iterator for Keys<'a> {
+	type Item = Address;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.inner.next().map(|(address, _)| address)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Values
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the cells in a `CellStorage`.
+#[derive(Debug)]
+pub struct Values<'a> {
+	/// The underlying iterator.
+	inner: Iter<'a>,
+}
+
+
+impl<'a> Iterator for Values<'a> {
+	type Item = &'a Shared<Cell>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.inner.next().map(|(_, cell)| cell)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// FreeSlots
+////////////////////////////////////////////////////////////////////////////////
+/// An index of the occupied addresses within a palette's layout, used to find
+/// free addresses without visiting every occupied address. An address is 
+/// occupied if its cell has a color. Occupied addresses are stored as runs of 
+/// addresses which are consecutive within the layout, so the index must be 
+/// rebuilt whenever the layout's shape changes.
+///
+/// Addresses whose colors may have changed are marked as stale, and are 
+/// checked again the next time the index is refreshed.
+#[derive(Debug, Clone)]
+pub(crate) struct FreeSlots {
+	/// The first and last address of each run of occupied addresses.
+	runs: BTreeMap<Address, Address>,
+	/// The addresses whose colors may have changed since they were indexed.
+	stale: BTreeSet<Address>,
+	/// The layout the index was built for. Holding it ensures that no other 
+	/// layout can share its address.
+	layout: Shared<Layout>,
+}
+
+
+impl FreeSlots {
+	/// Builds the index for the cells of the given `Data`.
+	pub(crate) fn new(data: &Data) -> FreeSlots {
+		let mut free_slots = FreeSlots {
+			runs: BTreeMap::new(),
+			stale: BTreeSet::new(),
+			layout: data.layout.clone(),
+		};
+
+		let mut run: Option<(Address, Address)> = None;
+		for address in data.cells.keys() {
+			if !data.check_address(address) || data.color(address).is_none() {
+				continue;
+			}
+			run = match run {
+				Some((start, end)) if next_address(data, end) == Some(address) 
+					=> Some((start, address)),
+				Some((start, end)) => {
+					free_slots.runs.insert(start, end);
+					Some((address, address))
+				},
+				None => Some((address, address)),
+			};
+		}
+		if let Some((start, end)) = run {
+			free_slots.runs.insert(start, end);
+		}
+		free_slots
+	}
+
+	/// Returns whether the index was built for the current layout of the given 
+	/// `Data`.
+	pub(crate) fn is_current(&self, data: &Data) -> bool {
+		layout_address(&self.layout) == layout_address(&data.layout)
+	}
+
+	/// Marks the given address as stale, so that it is checked again when the
+	/// index is next refreshed.
+	pub(crate) fn mark_stale(&mut self, address: Address) {
+		self.stale.insert(address);
+	}
+
+	/// Checks whether each stale address of the given `Data` is occupied.
+	pub(crate) fn refresh(&mut self, data: &Data) {
+		for address in mem::replace(&mut self.stale, BTreeSet::new()) {
+			if data.color(address).is_some() {
+				self.occupy(data, address);
+			} else {
+				self.vacate(data, address);
+			}
+		}
+	}
+
+	/// Marks the given address as occupied.
+	fn occupy(&mut self, data: &Data, address: Address) {
+		if !data.check_address(address) || 
+			self.run_containing(address).is_some()
+		{
+			return;
+		}
+
+		// Join the runs ending before and starting after the address.
+		let start = data.previous_valid_address(address)
+			.and_then(|previous| self.run_containing(previous)
+				.filter(|&(_, end)| end == previous))
+			.map_or(address, |(start, _)| start);
+		let end = next_address(data, address)
+			.and_then(|next| self.runs.remove(&next))
+			.unwrap_or(address);
+		self.runs.insert(start, end);
+	}
+
+	/// Marks the given address as free.
+	fn vacate(&mut self, data: &Data, address: Address) {
+		if !data.check_address(address) {return;}
+		let (start, end) = match self.run_containing(address) {
+			Some(run) => run,
+			None => return,
+		};
+
+		// Split the run containing the address.
+		self.runs.remove(&start);
+		if start < address {
+			let previous = data.previous_valid_address(address)
+				.expect("address follows start of run");
+			self.runs.insert(start, previous);
+		}
+		if address < end {
+			let next = next_address(data, address)
+				.expect("address precedes end of run");
+			self.runs.insert(next, end);
+		}
+	}
+
+	/// Returns the first free address at or after the given address within 
+	/// the layout, without wrapping around. The given address must lie within
+	/// the layout.
+	pub(crate) fn next_free(&self, data: &Data, address: Address) 
+		-> Option<Address>
+	{
+		match self.run_containing(address) {
+			Some((_, end)) => next_address(data, end),
+			None => Some(address),
+		}
+	}
+
+	/// Returns the run of occupied addresses containing the given address.
+	fn run_containing(&self, address: Address) -> Option<(Address, Address)> {
+		self.runs
+			.range(..=address)
+			.next_back()
+			.map(|(&start, &end)| (start, end))
+			.filter(|&(_, end)| end >= address)
+	}
+}
+
+
+/// Returns the address following the given address within the layout of the 
+/// given `Data`.
+fn next_address(data: &Data, address: Address) -> Option<Address> {
+	address.successor().and_then(|next| data.next_valid_address(next))
+}
+
+
+/// Returns the address of the given layout.
+fn layout_address(layout: &Shared<Layout>) -> *const () {
+	&**layout as *const Layout as *const ()
+}
@@ -0,0 +1,214 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides the shared pointer and lock types used by the palette. By default 
+//! these are the single-threaded `Rc` and `RefCell`. When the `sync` feature is
+//! enabled, they are replaced by `Arc` and `RwLock`, so that palettes may be 
+//! sent to and shared between threads.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "sync")] {
+//! use palette::*;
+//! use palette::operation::*;
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let mut pal = Palette::new("Example", Format::Default, true);
+//! pal.apply(Box::new(InsertCell::new()
+//! 	.with_expression(Expression::Color(Color::new(12, 50, 78)))
+//! )).unwrap();
+//! pal.apply(Box::new(InsertCell::new()
+//! 	.with_expression("lighten(0:0:0, 10%)".parse().unwrap())
+//! )).unwrap();
+//!
+//! let pal = Arc::new(pal);
+//! let workers: Vec<_> = (0..4)
+//! 	.map(|_| {
+//! 		let pal = pal.clone();
+//! 		thread::spawn(move || pal.color(Address::new(0, 0, 1)))
+//! 	})
+//! 	.collect();
+//!
+//! for worker in workers {
+//! 	assert_eq!(worker.join().unwrap(), pal.color(Address::new(0, 0, 1)));
+//! }
+//! # }
+//! ```
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Standard imports.
+#[cfg(not(feature = "sync"))]
+use std::cell::{Ref, RefCell, RefMut};
+#[cfg(feature = "sync")]
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Shared pointers
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(not(feature = "sync"))]
+pub use std::rc::{Rc as Shared, Weak as WeakShared};
+
+#[cfg(feature = "sync")]
+pub use std::sync::{Arc as Shared, Weak as WeakShared};
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Threadsafe
+////////////////////////////////////////////////////////////////////////////////
+/// A marker trait for types which must be shareable between threads when the
+/// `sync` feature is enabled. Without the feature, every type implements it.
+#[cfg(not(feature = "sync"))]
+pub trait Threadsafe {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> Threadsafe for T {}
+
+/// A marker trait for types which must be shareable between threads when the
+/// `sync` feature is enabled. Without the feature, every type implements it.
+#[cfg(feature = "sync")]
+pub trait Threadsafe: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> Threadsafe for T {}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lock
+////////////////////////////////////////////////////////////////////////////////
+/// A guard providing shared access to the value in a `Lock`.
+#[cfg(not(feature = "sync"))]
+pub type ReadGuard<'a, T> = Ref<'a, T>;
+
+/// A guard providing exclusive access to the value in a `Lock`.
+#[cfg(not(feature = "sync"))]
+pub type WriteGuard<'a, T> = RefMut<'a, T>;
+
+/// A guard providing shared access to the value in a `Lock`.
+#[cfg(feature = "sync")]
+pub type ReadGuard<'a, T> = RwLockReadGuard<'a, T>;
+
+/// A guard providing exclusive access to the value in a `Lock`.
+#[cfg(feature = "sync")]
+pub type WriteGuard<'a, T> = RwLockWriteGuard<'a, T>;
+
+
+/// A mutable memory location with dynamically checked borrows. This is a 
+/// `RefCell` by default, or a `RwLock` when the `sync` feature is enabled.
+#[derive(Debug, Default)]
+pub struct Lock<T> {
+	/// The wrapped value.
+	#[cfg(not(feature = "sync"))]
+	inner: RefCell<T>,
+
+	/// The wrapped value.
+	#[cfg(feature = "sync")]
+	inner: RwLock<T>,
+}
+
+
+impl<T> Lock<T> {
+	/// Creates a new `Lock` containing the given value.
+	#[cfg(not(feature = "sync"))]
+	pub fn new(value: T) -> Self {
+		Lock {inner: RefCell::new(value)}
+	}
+
+	/// Creates a new `Lock` containing the given value.
+	#[cfg(feature = "sync")]
+	pub fn new(value: T) -> Self {
+		Lock {inner: RwLock::new(value)}
+	}
+
+	/// Immutably borrows the wrapped value.
+	///
+	/// # Panics
+	///
+	/// Panics if the value is currently mutably borrowed.
+	#[cfg(not(feature = "sync"))]
+	pub fn borrow(&self) -> ReadGuard<T> {
+		self.inner.borrow()
+	}
+
+	/// Immutably borrows the wrapped value, blocking while it is mutably 
+	/// borrowed by another thread. A lock poisoned by a panicking thread is 
+	/// borrowed as usual.
+	///
+	/// # Panics
+	///
+	/// May panic if the value is currently mutably borrowed by the current 
+	/// thread.
+	///
+	/// # Deadlocks
+	///
+	/// `RwLock` does not detect borrows held by the current thread, so 
+	/// borrowing a value that the current thread already mutably borrows may 
+	/// deadlock instead of panicking. On some platforms, borrowing a value that
+	/// the current thread already immutably borrows may also deadlock if 
+	/// another thread is waiting to mutably borrow it.
+	#[cfg(feature = "sync")]
+	pub fn borrow(&self) -> ReadGuard<T> {
+		self.inner.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	/// Mutably borrows the wrapped value.
+	///
+	/// # Panics
+	///
+	/// Panics if the value is currently borrowed.
+	#[cfg(not(feature = "sync"))]
+	pub fn borrow_mut(&self) -> WriteGuard<T> {
+		self.inner.borrow_mut()
+	}
+
+	/// Mutably borrows the wrapped value, blocking while it is borrowed by 
+	/// another thread. A lock poisoned by a panicking thread is borrowed as 
+	/// usual.
+	///
+	/// # Panics
+	///
+	/// May panic if the value is currently borrowed by the current thread.
+	///
+	/// # Deadlocks
+	///
+	/// `RwLock` does not detect borrows held by the current thread, so 
+	/// borrowing a value that the current thread already borrows may deadlock 
+	/// instead of panicking.
+	#[cfg(feature = "sync")]
+	pub fn borrow_mut(&self) -> WriteGuard<T> {
+		self.inner.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+
+impl<T> Clone for Lock<T> where T: Clone {
+	fn clone(&self) -> Self {
+		Lock::new(self.borrow().clone())
+	}
+}
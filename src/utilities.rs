@@ -25,6 +25,11 @@
 //! Defines general purpose functions for palette use.
 //!
 ////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use colorspace::{linear_to_srgb, srgb_to_linear};
+
+// Standard imports.
 use std::f32;
 
 
@@ -144,3 +149,64 @@ pub fn lerp_f32(start: f32, end:f32, amount: f32) -> f32 {
 	let e = if start > end {start} else {end};
 	(((e-s) as f32) * a) as f32 + s
 }
+
+
+/// Performs a linear interpolation between `start` and `end` in the linear 
+/// space given by the `decode` transfer function, returning the value located 
+/// at the ratio given by `amount` re-encoded by the `encode` transfer 
+/// function. The `amount` is clamped between 0 and 1.
+///
+/// # Examples
+///
+/// ```rust
+/// # use palette::utilities::{lerp_f32_transfer, nearly_equal};
+/// let a = lerp_f32_transfer(0.0, 1.0, 0.25, |x| x * x, f32::sqrt);
+///
+/// assert!(nearly_equal(a, 0.5)); // a is 25% between 0 and 1 when squared.
+/// ```
+#[inline]
+pub fn lerp_f32_transfer<D, E>(
+	start: f32,
+	end: f32,
+	amount: f32,
+	decode: D,
+	encode: E)
+	-> f32
+	where
+		D: Fn(f32) -> f32,
+		E: Fn(f32) -> f32,
+{
+	encode(lerp_f32(decode(start), decode(end), amount))
+}
+
+
+/// Performs a linear interpolation between the sRGB encoded octets `start` 
+/// and `end` in linear light, returning the sRGB encoded value located at the
+/// ratio given by `amount`, which is clamped between 0 and 1.
+///
+/// # Examples
+///
+/// ```rust
+/// # use palette::utilities::lerp_u8_linear;
+/// let a = lerp_u8_linear(0, 255, 0.5);
+///
+/// assert_eq!(a, 188); // Half of the light of 255 is encoded as 188.
+/// ```
+///
+/// ```rust
+/// # use palette::utilities::lerp_u8_linear;
+/// let a = lerp_u8_linear(15, 200, 0.2);
+/// let b = lerp_u8_linear(200, 15, 0.8);
+///
+/// assert_eq!(a, b); // Reversed argument order inverts the ratio.
+/// ```
+#[inline]
+pub fn lerp_u8_linear(start: u8, end: u8, amount: f32) -> u8 {
+	let value = lerp_f32_transfer(
+		start as f32 / 255.0,
+		end as f32 / 255.0,
+		amount,
+		srgb_to_linear,
+		linear_to_srgb);
+	(clamped(value, 0.0, 1.0) * 255.0).round() as u8
+}